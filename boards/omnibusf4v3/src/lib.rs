@@ -78,6 +78,11 @@ fn board_name() -> &'static str {
     "OMNIBUSF4V3"
 }
 
+#[no_mangle]
+fn pwm_timer_groups() -> &'static [&'static [&'static str]] {
+    &pwm::TIMER_GROUPS
+}
+
 #[no_mangle]
 fn reboot() -> ! {
     cortex_m::peripheral::SCB::sys_reset()
@@ -1,9 +1,12 @@
-use core::cmp::min;
-
-use alloc::{boxed::Box, vec, vec::Vec};
+use alloc::{boxed::Box, vec::Vec};
 
+use drivers::stm32::dshot::{DmaDShot, SharedTimer, TimerChannel};
 use embedded_hal::PwmPin;
-use pro_flight::config::peripherals::pwm::PWMs as Config;
+use hal::dma::DMA;
+use pro_flight::{
+    config::peripherals::pwm::{PWMs as Config, Protocol, PWM},
+    servo::pwm::Output,
+};
 use stm32f4xx_hal::{
     gpio::{
         gpioa::{PA1, PA2, PA3, PA8},
@@ -17,40 +20,120 @@ use stm32f4xx_hal::{
 
 type PWMs = (pac::TIM1, pac::TIM2, pac::TIM3, pac::TIM5);
 type PINs = (PB0<Input>, PB1<Input>, PA2<Input>, PA3<Input>, PA1<Input>, PA8<Input>);
-type PwmPins = Vec<(&'static str, Box<dyn PwmPin<Duty = u16> + Send + 'static>)>;
+type PwmPins = Vec<(&'static str, Output)>;
+
+const TIM2_DMA_CHANNEL: u8 = 3;
+const TIM3_DMA_CHANNEL: u8 = 5;
+
+/// PWM1-2 driven by TIM3, PWM3-4 driven by TIM2
+pub static TIMER_GROUPS: [&[&str]; 2] = [&["PWM1", "PWM2"], &["PWM3", "PWM4"]];
+
+fn protocol(cfg: &Config, name: &str) -> Protocol {
+    match cfg.get(name) {
+        Some(PWM::Motor(motor)) => motor.protocol,
+        _ => Protocol::PWM,
+    }
+}
+
+/// Timer group runs at the rate its configured outputs agree on, DShot bit rate
+/// if any of them is DShot, conflicting group left disabled
+fn group_rate(cfg: &Config, group: &[&str]) -> Option<(u32, bool)> {
+    if let Err(error) = cfg.validate_group(group) {
+        error!("{:?} disabled: {}", group, error);
+        return None;
+    }
+    let dshot = group.iter().any(|name| protocol(cfg, name).bitrate().is_some());
+    let rate = group.iter().find_map(|name| cfg.get(name)).map(|o| o.timer_rate());
+    Some((rate.unwrap_or(50), dshot))
+}
+
+/// Output without DMA stream is disabled if configured DShot
+fn analog_rate(cfg: &Config, name: &str) -> Option<u32> {
+    if protocol(cfg, name).bitrate().is_some() {
+        error!("{} disabled: DShot available on PWM1-4 only", name);
+        return None;
+    }
+    group_rate(cfg, &[name]).map(|(rate, _)| rate)
+}
 
-pub fn init(pwms: PWMs, pins: PINs, clocks: &Clocks, cfg: &Config) -> PwmPins {
-    let rate1 = cfg.get("PWM1").map(|o| o.rate()).unwrap_or(50) as u32;
-    let rate2 = cfg.get("PWM2").map(|o| o.rate()).unwrap_or(50) as u32;
+/// Unconfigured output sharing timer with DShot output is disabled
+fn output<P: PwmPin<Duty = u16> + Send + 'static, D: DMA + Clone>(
+    cfg: &Config,
+    name: &'static str,
+    mut pwm: P,
+    dma: (D, u8, TimerChannel),
+    (rate, dshot_group): (u32, bool),
+) -> Option<(&'static str, Output)> {
+    pwm.enable();
+    let bidirectional = matches!(cfg.get(name), Some(PWM::Motor(m)) if m.bidirectional);
+    match protocol(cfg, name).bitrate() {
+        Some(_) => {
+            pwm.set_duty(0);
+            let (dma, channel, timer_channel) = dma;
+            let max_duty = pwm.get_max_duty();
+            let dshot = if bidirectional {
+                DmaDShot::bidirectional(dma, channel, timer_channel, max_duty)
+            } else {
+                DmaDShot::new(dma, channel, timer_channel, max_duty)
+            };
+            Some((name, Output::DShot(Box::new(dshot))))
+        }
+        None if dshot_group => {
+            warn!("{} disabled due to sharing timer with DShot output", name);
+            None
+        }
+        None => Some((name, Output::PWM(Box::new(pwm), rate))),
+    }
+}
 
+/// DShot available on PWM1-4 only, with DMA streams of TIM3 CH3, TIM3 CH4, TIM2 CH4, TIM2 CH3
+pub fn init<D1, D2, D3, D4>(
+    pwms: PWMs,
+    pins: PINs,
+    dmas: (D1, D2, D3, D4),
+    clocks: &Clocks,
+    cfg: &Config,
+) -> PwmPins
+where
+    D1: DMA + Clone,
+    D2: DMA + Clone,
+    D3: DMA + Clone,
+    D4: DMA + Clone,
+{
     let (tim1, tim2, tim3, tim5) = pwms;
     let (pb0, pb1, pa2, pa3, pa1, pa8) = pins;
-    let pb0_1 = (pb0.into_alternate(), pb1.into_alternate());
-    let rate = if rate1 > 0 && rate2 > 0 { min(rate1, rate2) } else { rate1 + rate2 };
-    let (pwm1, pwm2) = tim3.pwm_hz(pb0_1, rate.Hz(), clocks).split();
-
-    let rate3 = cfg.get("PWM3").map(|o| o.rate()).unwrap_or(50) as u32;
-    let rate4 = cfg.get("PWM4").map(|o| o.rate()).unwrap_or(50) as u32;
-    let pa2_3 = (pa2.into_alternate(), pa3.into_alternate());
-    let rate = if rate3 > 0 && rate4 > 0 { min(rate3, rate4) } else { rate3 + rate4 };
-    let (pwm4, pwm3) = tim2.pwm_hz(pa2_3, rate.Hz(), clocks).split();
-
-    let rate = cfg.get("PWM5").map(|o| o.rate()).unwrap_or(50) as u32;
-    let pwm5 = tim5.pwm_hz(pa1.into_alternate(), rate.Hz(), clocks).split();
-    let rate = cfg.get("PWM6").map(|o| o.rate()).unwrap_or(50) as u32;
-    let pwm6 = tim1.pwm_hz(pa8.into_alternate(), rate.Hz(), clocks).split();
-
-    let mut pwms: PwmPins = vec![
-        ("PWM1", Box::new(pwm1)),
-        ("PWM2", Box::new(pwm2)),
-        ("PWM3", Box::new(pwm3)),
-        ("PWM4", Box::new(pwm4)),
-        ("PWM5", Box::new(pwm5)),
-        ("PWM6", Box::new(pwm6)),
-    ];
-
-    for (_, pwm) in pwms.iter_mut() {
-        pwm.enable();
-    }
-    pwms
+    let (dma1, dma2, dma3, dma4) = dmas;
+    let mut outputs: PwmPins = Vec::new();
+
+    if let Some(group) = group_rate(cfg, TIMER_GROUPS[0]) {
+        let pb0_1 = (pb0.into_alternate(), pb1.into_alternate());
+        let (pwm1, pwm2) = tim3.pwm_hz(pb0_1, group.0.Hz(), clocks).split();
+        let timer = SharedTimer::new(pac::TIM3::ptr() as usize);
+        let dma = (dma1, TIM3_DMA_CHANNEL, TimerChannel::new(timer, 3));
+        outputs.extend(output(cfg, "PWM1", pwm1, dma, group));
+        let dma = (dma2, TIM3_DMA_CHANNEL, TimerChannel::new(timer, 4));
+        outputs.extend(output(cfg, "PWM2", pwm2, dma, group));
+    }
+
+    if let Some(group) = group_rate(cfg, TIMER_GROUPS[1]) {
+        let pa2_3 = (pa2.into_alternate(), pa3.into_alternate());
+        let (pwm4, pwm3) = tim2.pwm_hz(pa2_3, group.0.Hz(), clocks).split();
+        let timer = SharedTimer::new(pac::TIM2::ptr() as usize);
+        let dma = (dma3, TIM2_DMA_CHANNEL, TimerChannel::new(timer, 4));
+        outputs.extend(output(cfg, "PWM3", pwm3, dma, group));
+        let dma = (dma4, TIM2_DMA_CHANNEL, TimerChannel::new(timer, 3));
+        outputs.extend(output(cfg, "PWM4", pwm4, dma, group));
+    }
+
+    if let Some(rate) = analog_rate(cfg, "PWM5") {
+        let mut pwm5 = tim5.pwm_hz(pa1.into_alternate(), rate.Hz(), clocks).split();
+        pwm5.enable();
+        outputs.push(("PWM5", Output::PWM(Box::new(pwm5), rate)));
+    }
+    if let Some(rate) = analog_rate(cfg, "PWM6") {
+        let mut pwm6 = tim1.pwm_hz(pa8.into_alternate(), rate.Hz(), clocks).split();
+        pwm6.enable();
+        outputs.push(("PWM6", Output::PWM(Box::new(pwm6), rate)));
+    }
+    outputs
 }
@@ -1,6 +1,6 @@
 //! The root task.
 
-use alloc::boxed::Box;
+use alloc::{boxed::Box, vec::Vec};
 
 use chips::stm32f4::{
     adc::IntoDMA as _,
@@ -41,10 +41,13 @@ use pro_flight::{
     cli::CLI,
     config::{
         self,
-        peripherals::serial::{Config as SerialConfig, RemoteControl as RC},
+        peripherals::{
+            pwm::PWM,
+            serial::{Config as SerialConfig, RemoteControl as RC},
+        },
     },
     datastore,
-    fcs::FCS,
+    fcs::{self, FCS},
     imu::IMU,
     ins,
     ins::variometer::Variometer,
@@ -241,28 +244,56 @@ pub fn handler(reg: Regs, thr_init: ThrsInit) {
     info!("Initialize PWMs");
     let tims = (peripherals.TIM1, peripherals.TIM2, peripherals.TIM3, peripherals.TIM5);
     let pins = (gpio_b.pb0, gpio_b.pb1, gpio_a.pa2, gpio_a.pa3, gpio_a.pa1, gpio_a.pa8);
-    let pwms = crate::pwm::init(tims, pins, &clocks, &config::get().peripherals.pwms);
-    let mut servos = PWMs::new(pwms);
-    let mut fcs = FCS::new(SAMPLE_RATE);
-    threads.fcs.add_fn(fiber_yield(move || {
-        fcs.update();
-        servos.update();
-    }));
+    let dmas = (
+        dma::Stream::new(periph_dma1_ch7!(reg), threads.dma1_stream7),
+        dma::Stream::new(periph_dma1_ch2!(reg), threads.dma1_stream2),
+        dma::Stream::new(periph_dma1_ch6!(reg), threads.dma1_stream6),
+        dma::Stream::new(periph_dma1_ch1!(reg), threads.dma1_stream1),
+    );
+    let pwm_config = &config::get().peripherals.pwms;
+    let pwms = crate::pwm::init(tims, pins, dmas, &clocks, pwm_config);
+    let is_motor = |name: &str| matches!(pwm_config.get(name), Some(PWM::Motor(_)));
+    let (motors, servos): (Vec<_>, Vec<_>) = pwms.into_iter().partition(|(name, _)| is_motor(name));
+
+    // Sys tick limits motor rate to 1kHz
+    let motor_rate = core::cmp::min(pwm_config.motor_rate(), 1000);
+    let mut motors = PWMs::new(motors);
+    threads.motor.add_fn(fiber_yield(move || motors.update_motors()));
+    let waker = executor(threads.motor);
+    let motor_interval = Duration::micros(1_000_000 / motor_rate as u64);
+    let mut motors = Schedule::new(waker, TickTimer::default(), motor_interval);
+
+    let servo_interval = Duration::micros(1_000_000 / pwm_config.servo_rate() as u64);
+    let mut servos = PWMs::new(servos);
+    threads.servo.add_fn(fiber_yield(move || servos.update_servos()));
+    let waker = executor(threads.servo);
+    let mut servos = Schedule::new(waker, TickTimer::default(), servo_interval);
 
+    let fcs_rate = fcs::update_rate(config::get().fcs.configuration);
+    let mut fcs = FCS::new(fcs_rate);
+    threads.fcs.add_fn(fiber_yield(move || fcs.update()));
     let waker = executor(threads.fcs);
-    let mut servos = Schedule::new(waker, TickTimer::default(), Duration::millis(20));
+    let fcs_interval = Duration::micros(1_000_000 / fcs_rate as u64);
+    let mut fcs = Schedule::new(waker, TickTimer::default(), fcs_interval);
 
     threads.sys_tick.add_fn(fiber_yield(move || {
         bmp280.wakeup();
         max7456.wakeup();
+        fcs.wakeup();
+        motors.wakeup();
         servos.wakeup();
     }));
 
     let mut watchdog = IndependentWatchdog::new(peripherals.IWDG);
     watchdog.start(500.millis());
 
-    let commands =
-        commands!((bootloader, [persist]), (osd, [event]), (save, [nvram]), (telemetry, []));
+    let commands = commands!(
+        (bootloader, [persist]),
+        (esc, []),
+        (osd, [event]),
+        (save, [nvram]),
+        (telemetry, [])
+    );
     let mut cli = CLI::new(commands);
     loop {
         TickTimer::after(Duration::millis(1)).root_wait();
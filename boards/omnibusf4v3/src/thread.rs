@@ -28,13 +28,19 @@ thr::nvic! {
         };
         interrupts => {
             5: pub rcc;
+            6: pub servo; // exti0
             7: pub fcs; // exti1
             8: pub bmp280; // exti2
             9: pub max7456; // exti3
             10: pub mpu6000; // exti4
             11: pub dma1_stream0; // BMP280/MAX7456 rx
+            12: pub dma1_stream1; // TIM2 CH3 DShot
+            13: pub dma1_stream2; // TIM3 CH4 DShot
             16: pub dma1_stream5; // BMP280 tx
+            17: pub dma1_stream6; // TIM2 CH4 DShot
             23: pub ins; // exti5-9
+            40: pub motor; // exti10-15
+            47: pub dma1_stream7; // TIM3 CH3 DShot
             56: pub dma2_stream0; // mpu6000 rx
             57: pub dma2_stream1; // USART3/I2C-2
             58: pub dma2_stream2; // ADC2
@@ -55,8 +61,14 @@ macro_rules! priority {
 pub fn setup_priority(threads: &mut Thrs) {
     threads.otg_fs.set_priority(priority!(Priority::Immediate));
     threads.fcs.set_priority(priority!(Priority::Immediate));
+    threads.servo.set_priority(priority!(Priority::Immediate));
+    threads.motor.set_priority(priority!(Priority::Immediate));
     threads.dma1_stream0.set_priority(priority!(Priority::System));
     threads.dma1_stream5.set_priority(priority!(Priority::System));
+    threads.dma1_stream1.set_priority(priority!(Priority::Immediate));
+    threads.dma1_stream2.set_priority(priority!(Priority::Immediate));
+    threads.dma1_stream6.set_priority(priority!(Priority::Immediate));
+    threads.dma1_stream7.set_priority(priority!(Priority::Immediate));
     threads.dma2_stream0.set_priority(priority!(Priority::System));
     threads.dma2_stream1.set_priority(priority!(Priority::System));
     threads.dma2_stream3.set_priority(priority!(Priority::System));
@@ -3,7 +3,7 @@ extern crate log;
 use env_logger::Env;
 use std::io::Read;
 
-use pro_flight::{config, config::yaml::YamlParser};
+use pro_flight::{config, config::yaml::YamlParser, fcs};
 
 fn init<'a>(matches: &'a clap::ArgMatches<'a>) -> Result<simulator::Config, String> {
     let config_path = matches.value_of("config").unwrap_or("simulator.yaml");
@@ -19,7 +19,11 @@ fn init<'a>(matches: &'a clap::ArgMatches<'a>) -> Result<simulator::Config, Stri
     let altimeter_rate = rate_str.parse::<usize>().map_err(|_| format!("Rate not a number"))?;
     let rate_str = matches.value_of("gnss-rate").unwrap_or("10");
     let gnss_rate = rate_str.parse::<usize>().map_err(|_| format!("Rate not a number"))?;
-    Ok(simulator::Config { sample_rate, altimeter_rate, gnss_rate })
+    let fcs_rate = match matches.value_of("fcs-rate") {
+        Some(rate_str) => rate_str.parse::<usize>().map_err(|_| format!("Rate not a number"))?,
+        None => fcs::update_rate(config.fcs.configuration),
+    };
+    Ok(simulator::Config { sample_rate, altimeter_rate, gnss_rate, fcs_rate })
 }
 
 macro_rules! arg {
@@ -43,6 +47,7 @@ async fn main() -> std::io::Result<()> {
         .arg(arg!("rate", "IMU sample rate"))
         .arg(arg!("altimeter-rate", "Altimeter sample rate"))
         .arg(arg!("gnss-rate", "GNSS sample rate"))
+        .arg(arg!("fcs-rate", "Control input rate, which FCS updates on"))
         .get_matches();
     let config = match init(&matches) {
         Ok(config) => config,
@@ -15,6 +15,7 @@ pub struct Config {
     pub sample_rate: usize,
     pub altimeter_rate: usize,
     pub gnss_rate: usize,
+    pub fcs_rate: usize,
 }
 
 pub struct Simulator {
@@ -31,7 +32,7 @@ impl Simulator {
         imu.skip_calibration();
         let variometer = Variometer::new(1000 / config.altimeter_rate);
         let ins = INS::new(config.sample_rate, variometer);
-        let mut fcs = FCS::new(1000);
+        let mut fcs = FCS::new(config.fcs_rate);
         fcs.update();
         Self { imu, ins, fcs, acceleration: None, gyro: None }
     }
@@ -42,7 +43,8 @@ impl Simulator {
 
     pub fn update_input(&mut self, axes: control::Axes) {
         let ds = datastore::acquire();
-        ds.write_control(control::Control { rssi: 100, axes, commands: Default::default() });
+        let control = control::Control { rssi: 100, link_quality: 100, axes, ..Default::default() };
+        ds.write_control(control);
         self.fcs.update();
     }
 
@@ -213,6 +213,7 @@ impl<M: DmaChMap> DMA for Stream<M> {
                 }
                 r.msize().write(v, msize);
                 r.dir().write(v, Direction::MemoryToPeripheral as u32);
+                r.tcie().set(v);
                 r.en().set(v);
             });
             Ok(DMABusy(self.reg.configuration))
@@ -247,6 +248,7 @@ impl<M: DmaChMap> DMA for Stream<M> {
                     r.circ().clear(v);
                 }
                 r.dir().write(v, Direction::PeripheralToMemory as u32);
+                r.tcie().set(v);
                 r.en().set(v);
                 if option.enable_half {
                     r.htie().set(v);
@@ -0,0 +1,194 @@
+use alloc::boxed::Box;
+use core::{
+    mem, ptr,
+    sync::atomic::{AtomicBool, AtomicU8, Ordering},
+};
+
+use hal::dma::{BufferDescriptor, Peripheral, TransferOption, TransferResult, DMA};
+use pro_flight::servo::dshot::{self, DShot, FRAME_SIZE};
+
+const CR1_OFFSET: usize = 0x00;
+const DIER_OFFSET: usize = 0x0C;
+const CCMR1_OFFSET: usize = 0x18;
+const CCER_OFFSET: usize = 0x20;
+const CNT_OFFSET: usize = 0x24;
+const ARR_OFFSET: usize = 0x2C;
+const CCR1_OFFSET: usize = 0x34;
+const CAPTURE_SIZE: usize = 32;
+const NO_EDGE: u32 = u32::MAX;
+
+unsafe fn modify(base: usize, offset: usize, clear: u32, set: u32) {
+    let register = (base + offset) as *mut u32;
+    ptr::write_volatile(register, ptr::read_volatile(register) & !clear | set)
+}
+
+/// Auto-reload is timer wide, so it is switched between bit period and capture
+/// once per cycle for all channels, counting frames still being sent
+pub struct SharedTimer {
+    base: usize,
+    sending: AtomicU8,
+    capturing: AtomicBool,
+}
+
+impl SharedTimer {
+    pub fn new(base: usize) -> &'static Self {
+        let (sending, capturing) = (AtomicU8::new(0), AtomicBool::new(false));
+        let timer = Box::leak(Box::new(Self { base, sending, capturing }));
+        // Auto-reload preload, new period takes effect on next update event
+        unsafe { modify(base, CR1_OFFSET, 0, 1 << 7) };
+        timer
+    }
+
+    /// First frame of a cycle restores bit period, replies of previous cycle are
+    /// long done by now and no frame is in flight, so counter is pushed to overflow
+    /// for the preloaded period to take effect on next tick
+    fn begin_frame(&self, period: u16) {
+        if self.capturing.swap(false, Ordering::Relaxed) {
+            unsafe {
+                modify(self.base, ARR_OFFSET, 0xFFFF_FFFF, period as u32);
+                modify(self.base, CNT_OFFSET, 0xFFFF_FFFF, 0xFFFF);
+            }
+        }
+        self.sending.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Last frame of a cycle widens auto-reload for free running capture,
+    /// taking effect once its final bit ends
+    fn end_frame(&self) {
+        if self.sending.fetch_sub(1, Ordering::Relaxed) == 1 {
+            unsafe { modify(self.base, ARR_OFFSET, 0xFFFF_FFFF, 0xFFFF) };
+            self.capturing.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Capture/compare register of timer channel as DMA destination, channel starts from 1
+#[derive(Copy, Clone)]
+pub struct TimerChannel {
+    timer: &'static SharedTimer,
+    channel: u8,
+}
+
+impl TimerChannel {
+    pub fn new(timer: &'static SharedTimer, channel: u8) -> Self {
+        Self { timer, channel }
+    }
+
+    unsafe fn modify(&self, offset: usize, clear: u32, set: u32) {
+        modify(self.timer.base, offset, clear, set)
+    }
+
+    fn ccmr_offset(&self) -> (usize, u32) {
+        let index = (self.channel as usize - 1) / 2;
+        (CCMR1_OFFSET + index * 4, 8 * ((self.channel as u32 - 1) % 2))
+    }
+
+    /// PWM mode 1 with preload, active low since bidirectional DShot idles high
+    fn output_mode(&self) {
+        let (ccmr, shift) = self.ccmr_offset();
+        let ccer = 4 * (self.channel as u32 - 1);
+        unsafe {
+            self.modify(CCER_OFFSET, 0b1011 << ccer, 0);
+            self.modify(ccmr, 0xFF << shift, (0b110 << 4 | 1 << 3) << shift);
+            self.modify(CCER_OFFSET, 0, 0b0011 << ccer);
+        }
+    }
+
+    /// Captures both edges, counter runs free once last frame on timer sent
+    fn input_mode(&self) {
+        let (ccmr, shift) = self.ccmr_offset();
+        let ccer = 4 * (self.channel as u32 - 1);
+        unsafe {
+            self.modify(CCER_OFFSET, 0b1011 << ccer, 0);
+            self.modify(ccmr, 0xFF << shift, 0b01 << shift);
+            self.modify(CCER_OFFSET, 0, 0b1011 << ccer);
+        }
+    }
+}
+
+impl Peripheral for TimerChannel {
+    fn enable_dma(&mut self) {
+        unsafe { self.modify(DIER_OFFSET, 0, 1 << (8 + self.channel)) }
+    }
+
+    fn address(&mut self) -> usize {
+        self.timer.base + CCR1_OFFSET + (self.channel as usize - 1) * mem::size_of::<u32>()
+    }
+
+    fn word_size(&self) -> usize {
+        mem::size_of::<u32>()
+    }
+}
+
+type Capture = BufferDescriptor<u32, CAPTURE_SIZE>;
+
+/// Timer runs at DShot bit rate, DMA feeds compare value of next bit on each compare match.
+/// In bidirectional mode the channel turns into input capture once frame sent,
+/// auto-reload of timer is widened after frames of all channels sent, so channels
+/// sharing the timer shall all be bidirectional DShot.
+pub struct DmaDShot<D> {
+    dma: D,
+    timer: TimerChannel,
+    bd: Box<BufferDescriptor<u32, FRAME_SIZE>>,
+    capture: Option<&'static mut Capture>,
+    max_duty: u16,
+    erpm: Option<u32>,
+}
+
+impl<D: DMA> DmaDShot<D> {
+    pub fn new(mut dma: D, dma_channel: u8, mut timer: TimerChannel, max_duty: u16) -> Self {
+        dma.setup_peripheral(dma_channel, &mut timer);
+        let bd = Box::new(BufferDescriptor::default());
+        Self { dma, timer, bd, capture: None, max_duty, erpm: None }
+    }
+
+    pub fn bidirectional(dma: D, dma_channel: u8, timer: TimerChannel, max_duty: u16) -> Self
+    where
+        D: Clone,
+    {
+        let mut dshot = Self::new(dma, dma_channel, timer, max_duty);
+        let capture = Box::leak(Box::new(Capture::new([NO_EDGE; CAPTURE_SIZE])));
+        let address = &mut *capture as *mut Capture as usize;
+        let rx = dshot.dma.clone();
+        let callback = move |_: TransferResult<u32>| {
+            timer.input_mode();
+            timer.timer.end_frame();
+            let capture = unsafe { &mut *(address as *mut Capture) };
+            rx.rx(capture, TransferOption::default()).ok();
+        };
+        dshot.bd = Box::new(BufferDescriptor::with_callback(callback));
+        dshot.capture = Some(capture);
+        timer.output_mode();
+        dshot
+    }
+}
+
+impl<D: DMA> DShot for DmaDShot<D> {
+    fn send(&mut self, frame: u16) {
+        let mut buffer = match self.bd.try_get_buffer() {
+            Ok(buffer) => buffer,
+            Err(_) => return, // Previous frame still transferring
+        };
+        if let Some(ref mut capture) = self.capture {
+            self.dma.stop();
+            self.timer.timer.begin_frame(self.max_duty);
+            self.timer.output_mode();
+            let edges = unsafe { capture.get_buffer() };
+            let size = edges.iter().position(|&edge| edge == NO_EDGE).unwrap_or(CAPTURE_SIZE);
+            // Reply runs at 5/4 of DShot bit rate
+            self.erpm = dshot::decode_erpm(&edges[..size], self.max_duty as u32 * 4 / 5);
+            if let Ok(mut buffer) = capture.try_get_buffer() {
+                buffer.fill(NO_EDGE);
+            }
+        }
+        dshot::encode(frame, self.max_duty, &mut buffer);
+        mem::drop(buffer);
+        if self.dma.tx(&self.bd, TransferOption::default()).is_err() && self.capture.is_some() {
+            self.timer.timer.end_frame();
+        }
+    }
+
+    fn erpm(&mut self) -> Option<u32> {
+        self.erpm
+    }
+}
@@ -1,3 +1,4 @@
+pub mod dshot;
 pub mod usart;
 pub mod usb_serial;
 pub mod voltage_adc;
@@ -0,0 +1,68 @@
+use core::f32::consts::PI;
+
+#[cfg(not(any(test, feature = "std")))]
+use micromath::F32Ext;
+
+/// Second order IIR filter in transposed direct form II
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    z1: f32,
+    z2: f32,
+}
+
+impl Biquad {
+    pub fn notch(sample_rate: f32, freq: f32, q: f32) -> Self {
+        let mut biquad = Self::default();
+        biquad.retune(sample_rate, freq, q);
+        biquad
+    }
+
+    /// Moves notch center frequency, filter state is kept to avoid glitch
+    pub fn retune(&mut self, sample_rate: f32, freq: f32, q: f32) {
+        let omega = 2.0 * PI * freq / sample_rate;
+        let (sin, cos) = (omega.sin(), omega.cos());
+        let alpha = sin / (2.0 * q);
+        let a0 = 1.0 + alpha;
+        self.b0 = 1.0 / a0;
+        self.b1 = -2.0 * cos / a0;
+        self.b2 = self.b0;
+        self.a1 = self.b1;
+        self.a2 = (1.0 - alpha) / a0;
+    }
+
+    pub fn filter(&mut self, sample: f32) -> f32 {
+        let output = self.b0 * sample + self.z1;
+        self.z1 = self.b1 * sample - self.a1 * output + self.z2;
+        self.z2 = self.b2 * sample - self.a2 * output;
+        output
+    }
+}
+
+mod test {
+    #[test]
+    fn test_notch() {
+        use core::f32::consts::PI;
+
+        use super::Biquad;
+
+        let amplitude = |freq: f32| {
+            let mut notch = Biquad::notch(1000.0, 100.0, 3.0);
+            let mut peak = 0.0f32;
+            for i in 0..1000 {
+                let output = notch.filter((2.0 * PI * freq * i as f32 / 1000.0).sin());
+                if i >= 900 {
+                    peak = peak.max(output.abs());
+                }
+            }
+            peak
+        };
+        assert!(amplitude(100.0) < 0.05);
+        assert!(amplitude(20.0) > 0.9);
+        assert!(amplitude(300.0) > 0.9);
+    }
+}
@@ -0,0 +1,68 @@
+use core::f32::consts::PI;
+
+#[cfg(not(any(test, feature = "std")))]
+use micromath::F32Ext;
+
+/// In-place radix-2 FFT, length of real and imag must be same power of two
+pub fn fft(real: &mut [f32], imag: &mut [f32]) {
+    let size = real.len();
+    let mut j = 0;
+    for i in 1..size {
+        let mut bit = size >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            real.swap(i, j);
+            imag.swap(i, j);
+        }
+    }
+
+    let mut length = 2;
+    while length <= size {
+        let half = length / 2;
+        for k in 0..half {
+            let angle = -2.0 * PI * k as f32 / length as f32;
+            let (sin, cos) = (angle.sin(), angle.cos());
+            for start in (0..size).step_by(length) {
+                let (a, b) = (start + k, start + k + half);
+                let re = real[b] * cos - imag[b] * sin;
+                let im = real[b] * sin + imag[b] * cos;
+                real[b] = real[a] - re;
+                imag[b] = imag[a] - im;
+                real[a] += re;
+                imag[a] += im;
+            }
+        }
+        length <<= 1;
+    }
+}
+
+mod test {
+    #[test]
+    fn test_fft() {
+        use core::f32::consts::PI;
+
+        use super::fft;
+
+        let mut real = [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+        let mut imag = [0.0; 8];
+        fft(&mut real, &mut imag);
+        assert_eq!(real, [1.0; 8]);
+        assert_eq!(imag, [0.0; 8]);
+
+        let mut real = [0.0f32; 16];
+        for (i, value) in real.iter_mut().enumerate() {
+            *value = (2.0 * PI * 3.0 * i as f32 / 16.0).cos();
+        }
+        let mut imag = [0.0; 16];
+        fft(&mut real, &mut imag);
+        for i in 0..16 {
+            let magnitude = (real[i] * real[i] + imag[i] * imag[i]).sqrt();
+            let expected = if i == 3 || i == 13 { 8.0 } else { 0.0 };
+            assert!((magnitude - expected).abs() < 0.05);
+        }
+    }
+}
@@ -1,3 +1,5 @@
+pub mod biquad;
+pub mod fft;
 pub mod lpf;
 pub mod mahony;
 
@@ -18,6 +18,9 @@ pub fn set(line: &str) {
             Ok(()) => (),
             Err(e) => println!("{}", e),
         }
+        if let Err(e) = config.validate(unsafe { super::pwm_timer_groups() }) {
+            println!("{}, save refused until resolved", e);
+        }
         config::replace(&config);
     }
 }
@@ -45,7 +48,9 @@ pub fn import(line: &str) {
     };
     match File::open(path) {
         Ok(mut file) => {
-            config::load(&mut file);
+            if let Err(e) = config::load(&mut file) {
+                println!("Import failed: {:?}", e);
+            }
             file.close();
         }
         Err(e) => {
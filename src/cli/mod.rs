@@ -27,6 +27,7 @@ extern "Rust" {
     fn board_name() -> &'static str;
     fn heap_statistics();
     pub fn reboot();
+    pub fn pwm_timer_groups() -> &'static [&'static [&'static str]];
 }
 
 macro_rules! __builtin_commands {
@@ -76,6 +77,11 @@ Usage:
     osd upload-font: Upload font
 "};
 
+pub const ESC_CMD_USAGE: &str = indoc! {"
+Usage:
+    esc <beep|beep1-5|normal|reversed|save>: Send DShot command when disarmed
+"};
+
 #[macro_export]
 macro_rules! __command {
     (bootloader,[$persist:ident]) => {
@@ -101,12 +107,25 @@ macro_rules! __command {
     };
     (save,[$nvram:ident]) => {
         $crate::cli::Command::new("save", "Save configuration", move |_| {
+            let timer_groups = unsafe { $crate::cli::pwm_timer_groups() };
+            if let Err(err) = config::get().validate(timer_groups) {
+                println!("Save configuration refused: {}", err);
+                return;
+            }
             if let Some(err) = $nvram.store(config::get()).err() {
                 println!("Save configuration failed: {:?}", err);
                 $nvram.reset().ok();
             }
         })
     };
+    (esc,[]) => {
+        $crate::cli::Command::new("esc", "Send DShot ESC command", move |cmd| {
+            match cmd.trim().parse() {
+                Ok(command) => $crate::servo::dshot::request(command),
+                Err(_) => println!("{}", $crate::cli::ESC_CMD_USAGE),
+            }
+        })
+    };
     (telemetry,[]) => {
         $crate::cli::Command::new("telemetry", "Show flight data", move |_| {
             let ds = $crate::datastore::acquire();
@@ -6,9 +6,11 @@ use crate::{
     imu::out::IMU,
     ins::out::INS,
     protocol::serial::gnss::out::GNSS,
+    servo::out::MotorRPM,
     types::{
         control::Control,
         measurement::{voltage::Voltage, Altitude},
+        waypoint::Steerpoint,
     },
 };
 
@@ -21,6 +23,9 @@ pub struct Collection {
     pub gnss: GNSS,
     pub imu: IMU,
     pub ins: INS,
+    #[serde(rename = "motor-rpm")]
+    pub motor_rpm: MotorRPM,
+    pub steerpoint: Steerpoint,
     pub voltage: Voltage,
 }
 
@@ -45,6 +50,8 @@ impl<'a> Collector<'a> {
             gnss: self.0.read_gnss(),
             imu: self.0.read_imu(),
             ins: self.0.read_ins(),
+            motor_rpm: self.0.read_motor_rpm_within(Duration::millis(100)).unwrap_or_default(),
+            steerpoint: self.0.read_steerpoint(),
             voltage: self.0.read_voltage(),
         }
     }
@@ -0,0 +1,20 @@
+use super::pathset::{Error, Path, PathSet, Value};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Arming {
+    pub require_gnss_fix: bool,
+    /// Arms once pre-arm checks pass when no arm toggle configured, for bench or simulator
+    pub auto_arm: bool,
+}
+
+impl PathSet for Arming {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "require-gnss-fix" => self.require_gnss_fix = value.parse_or(false)?,
+            "auto-arm" => self.auto_arm = value.parse_or(false)?,
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
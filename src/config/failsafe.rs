@@ -0,0 +1,40 @@
+use super::pathset::{Error, Path, PathSet, Value};
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Failsafe {
+    pub timeout: u16,       // milliseconds without control before failsafe
+    pub rssi_threshold: u8, // RSSI percentage below which failsafe, 0 to disable
+    pub hold_time: u8,      // seconds holding attitude before return
+    pub safe_altitude: u16, // meters above home
+    pub loiter_radius: u16, // meters orbiting home after return
+    pub throttle: u8,       // percentage once hold time elapsed, multirotor descends with it
+}
+
+impl Default for Failsafe {
+    fn default() -> Self {
+        Self {
+            timeout: 500,
+            rssi_threshold: 0,
+            hold_time: 5,
+            safe_altitude: 50,
+            loiter_radius: 100,
+            throttle: 50,
+        }
+    }
+}
+
+impl PathSet for Failsafe {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "timeout" => self.timeout = core::cmp::max(value.parse_or(500)?, 1),
+            "rssi-threshold" => self.rssi_threshold = value.parse_or(0)?,
+            "hold-time" => self.hold_time = value.parse_or(5)?,
+            "safe-altitude" => self.safe_altitude = value.parse_or(50)?,
+            "loiter-radius" => self.loiter_radius = core::cmp::max(value.parse_or(100)?, 10),
+            "throttle" => self.throttle = core::cmp::min(value.parse_or(50)?, 100),
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
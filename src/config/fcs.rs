@@ -1,8 +1,12 @@
 use core::str::FromStr;
 
 use fixed_point::FixedPoint;
+use heapless::Vec;
 
-use super::pathset::{Error, Path, PathSet, Value};
+use super::{
+    mixer::Mixer,
+    pathset::{Error, Path, PathClear, PathSet, Value},
+};
 
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[repr(u8)]
@@ -11,6 +15,16 @@ pub enum Configuration {
     Airplane,
     FlyingWing,
     VTail,
+    QuadX,
+    QuadPlus,
+    HexX,
+    Custom,
+}
+
+impl Configuration {
+    pub fn is_multirotor(&self) -> bool {
+        matches!(self, Self::QuadX | Self::QuadPlus | Self::HexX)
+    }
 }
 
 impl FromStr for Configuration {
@@ -21,11 +35,65 @@ impl FromStr for Configuration {
             "airplane" => Ok(Self::Airplane),
             "flying-wing" => Ok(Self::FlyingWing),
             "v-tail" => Ok(Self::VTail),
+            "quad-x" => Ok(Self::QuadX),
+            "quad-plus" => Ok(Self::QuadPlus),
+            "hex-x" => Ok(Self::HexX),
+            "custom" => Ok(Self::Custom),
             _ => Err(()),
         }
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Engines {
+    pub count: u8,
+    pub differential_thrust: u8, // percentage of yaw mixed into left and right engines
+}
+
+impl Default for Engines {
+    fn default() -> Self {
+        Self { count: 1, differential_thrust: 0 }
+    }
+}
+
+impl PathSet for Engines {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "count" => self.count = value.parse_or(1)?.clamp(1, 4),
+            "differential-thrust" => {
+                self.differential_thrust = core::cmp::min(value.parse_or(0)?, 100)
+            }
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Flaps {
+    pub max_angle: u8, // degrees of full flaps
+    pub flaperon: u8,  // percentage of flaps deflection drooping flaperons
+}
+
+impl Default for Flaps {
+    fn default() -> Self {
+        Self { max_angle: 40, flaperon: 50 }
+    }
+}
+
+impl PathSet for Flaps {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "max-angle" => self.max_angle = core::cmp::min(value.parse_or(40)?, 90),
+            "flaperon" => self.flaperon = core::cmp::min(value.parse_or(50)?, 100),
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct LPF {
     pub frequency: u16,
@@ -47,29 +115,113 @@ impl PathSet for LPF {
     }
 }
 
+/// Dynamic notch tracking gyro vibration peak within [min, max] frequency
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Notch {
+    pub enable: bool,
+    pub min_frequency: u16,
+    pub max_frequency: u16,
+    pub q: FixedPoint<u16, 2>,
+}
+
+impl Default for Notch {
+    fn default() -> Self {
+        Self { enable: false, min_frequency: 80, max_frequency: 400, q: fixed_point::fixed!(3.0) }
+    }
+}
+
+impl PathSet for Notch {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "enable" => self.enable = value.parse_or(false)?,
+            "min-frequency" => self.min_frequency = core::cmp::max(value.parse_or(80)?, 1),
+            "max-frequency" => self.max_frequency = core::cmp::max(value.parse_or(400)?, 1),
+            "q" => self.q = value.parse_or(fixed_point::fixed!(3.0))?,
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
+/// Notch filters following motor RPM reported by bidirectional DShot
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RPMNotch {
+    pub enable: bool,
+    pub min_frequency: u16,
+    pub q: FixedPoint<u16, 2>,
+}
+
+impl Default for RPMNotch {
+    fn default() -> Self {
+        Self { enable: false, min_frequency: 100, q: fixed_point::fixed!(5.0) }
+    }
+}
+
+impl PathSet for RPMNotch {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "enable" => self.enable = value.parse_or(false)?,
+            "min-frequency" => self.min_frequency = core::cmp::max(value.parse_or(100)?, 1),
+            "q" => self.q = value.parse_or(fixed_point::fixed!(5.0))?,
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
 #[derive(Copy, Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct Gyroscope {
     #[serde(rename = "LPF")]
     pub lpf: LPF,
+    pub notch: Notch,
+    pub rpm_notch: RPMNotch,
 }
 
 impl PathSet for Gyroscope {
     fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
         match path.str()? {
             "LPF" => self.lpf.set(path, value)?,
+            "notch" => self.notch.set(path, value)?,
+            "rpm-notch" => self.rpm_notch.set(path, value)?,
             _ => return Err(Error::UnknownPath),
         }
         Ok(())
     }
 }
 
-#[derive(Copy, Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct PID {
-    #[serde(rename = "max-rate")]
     pub max_rate: u16,
     pub kp: FixedPoint<u16, 2>,
     pub ki: FixedPoint<u16, 2>,
     pub kd: FixedPoint<u16, 2>,
+    pub kff: FixedPoint<u16, 2>,
+    pub p_limit: u8, // percentage of control-surface
+    pub i_limit: u8,
+    pub d_limit: u8,
+    pub i_relax: u8, // degree per second of setpoint change pausing I-term, 0 to disable
+    pub d_lpf: u16,  // hz, 0 to disable
+}
+
+impl Default for PID {
+    fn default() -> Self {
+        Self {
+            max_rate: 0,
+            kp: Default::default(),
+            ki: Default::default(),
+            kd: Default::default(),
+            kff: Default::default(),
+            p_limit: 100,
+            i_limit: 10,
+            d_limit: 10,
+            i_relax: 0,
+            d_lpf: 0,
+        }
+    }
 }
 
 impl PathSet for PID {
@@ -79,6 +231,12 @@ impl PathSet for PID {
             "kp" => self.kp = value.parse_or(fixed_point::fixed!(1.0))?,
             "ki" => self.ki = value.parse_or(fixed_point::fixed!(1.0))?,
             "kd" => self.kd = value.parse_or(fixed_point::fixed!(1.0))?,
+            "kff" => self.kff = value.parse_or_default()?,
+            "p-limit" => self.p_limit = core::cmp::min(value.parse_or(100)?, 100),
+            "i-limit" => self.i_limit = core::cmp::min(value.parse_or(10)?, 100),
+            "d-limit" => self.d_limit = core::cmp::min(value.parse_or(10)?, 100),
+            "i-relax" => self.i_relax = value.parse_or_default()?,
+            "d-lpf" => self.d_lpf = value.parse_or_default()?,
             _ => return Err(Error::UnknownPath),
         }
         Ok(())
@@ -100,18 +258,21 @@ impl Default for PIDs {
                 kp: fixed_point::fixed!(0.44),
                 ki: fixed_point::fixed!(0.4),
                 kd: fixed_point::fixed!(0.2),
+                ..Default::default()
             },
             pitch: PID {
                 max_rate: 30,
                 kp: fixed_point::fixed!(0.58),
                 ki: fixed_point::fixed!(0.5),
                 kd: fixed_point::fixed!(0.22),
+                ..Default::default()
             },
             yaw: PID {
                 max_rate: 30,
                 kp: fixed_point::fixed!(0.7),
                 ki: fixed_point::fixed!(0.45),
                 kd: fixed_point::fixed!(0.2),
+                ..Default::default()
             },
         }
     }
@@ -134,11 +295,21 @@ pub struct Envelop {
     pub max_roll: u8,
     pub min_pitch: i8,
     pub max_pitch: i8,
+    pub stall_speed: u8,  // meters per second, 0 to disable
+    pub stall_margin: u8, // percentage above stall speed where protection begins
+    pub max_speed: u8,    // meters per second, 0 to disable
 }
 
 impl Default for Envelop {
     fn default() -> Self {
-        Self { max_roll: 67, min_pitch: -15, max_pitch: 30 }
+        Self {
+            max_roll: 67,
+            min_pitch: -15,
+            max_pitch: 30,
+            stall_speed: 0,
+            stall_margin: 20,
+            max_speed: 0,
+        }
     }
 }
 
@@ -148,27 +319,418 @@ impl PathSet for Envelop {
             "max-roll" => self.max_roll = value.parse()?,
             "min-pitch" => self.min_pitch = value.parse()?,
             "max-pitch" => self.max_pitch = value.parse()?,
+            "stall-speed" => self.stall_speed = value.parse_or_default()?,
+            "stall-margin" => self.stall_margin = value.parse_or(20)?,
+            "max-speed" => self.max_speed = value.parse_or_default()?,
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
+/// Yaw rate setpoint following bank angle, for airplane and v-tail
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TurnCoordination {
+    pub enable: bool,
+    pub min_speed: u8, // meters per second, lower bound of speed in yaw rate calculation
+    pub gain: u8,      // percentage
+}
+
+impl Default for TurnCoordination {
+    fn default() -> Self {
+        Self { enable: false, min_speed: 10, gain: 100 }
+    }
+}
+
+impl PathSet for TurnCoordination {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "enable" => self.enable = value.parse_or(false)?,
+            "min-speed" => self.min_speed = core::cmp::max(value.parse_or(10)?, 1),
+            "gain" => self.gain = value.parse_or(100)?,
             _ => return Err(Error::UnknownPath),
         }
         Ok(())
     }
 }
 
+/// Hand or bungee launch, detected by acceleration once armed
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Launch {
+    pub enable: bool,
+    pub threshold: FixedPoint<u16, 2>, // G
+    pub motor_delay: u16,              // milliseconds after throw before motor spin-up
+    pub climb_pitch: u8,               // degree
+    pub duration: u8,                  // seconds holding climb attitude
+    pub throttle: u8,                  // percentage
+}
+
+impl Default for Launch {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            threshold: fixed_point::fixed!(2.5),
+            motor_delay: 500,
+            climb_pitch: 15,
+            duration: 5,
+            throttle: 100,
+        }
+    }
+}
+
+impl PathSet for Launch {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "enable" => self.enable = value.parse_or(false)?,
+            "threshold" => self.threshold = value.parse_or(fixed_point::fixed!(2.5))?,
+            "motor-delay" => self.motor_delay = value.parse_or(500)?,
+            "climb-pitch" => self.climb_pitch = core::cmp::min(value.parse_or(15)?, 45),
+            "duration" => self.duration = value.parse_or(5)?,
+            "throttle" => self.throttle = core::cmp::min(value.parse_or(100)?, 100),
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
+const DEFAULT_AUTO_THROTTLE_KP: FixedPoint<u16, 2> = fixed_point::fixed!(5.0, 2);
+const DEFAULT_AUTO_THROTTLE_KI: FixedPoint<u16, 2> = fixed_point::fixed!(1.0, 2);
+const DEFAULT_AUTO_THROTTLE_KD: FixedPoint<u16, 2> = fixed_point::fixed!(0.1, 2);
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AutoThrottle {
+    pub kp: FixedPoint<u16, 2>, // throttle percentage per m/s
+    pub ki: FixedPoint<u16, 2>,
+    pub kd: FixedPoint<u16, 2>,
+    pub slew_rate: u8, // throttle percentage per second
+    pub min_throttle: u8,
+    pub max_throttle: u8,
+}
+
+impl Default for AutoThrottle {
+    fn default() -> Self {
+        Self {
+            kp: DEFAULT_AUTO_THROTTLE_KP,
+            ki: DEFAULT_AUTO_THROTTLE_KI,
+            kd: DEFAULT_AUTO_THROTTLE_KD,
+            slew_rate: 20,
+            min_throttle: 10,
+            max_throttle: 100,
+        }
+    }
+}
+
+impl PathSet for AutoThrottle {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "kp" => self.kp = value.parse_or(DEFAULT_AUTO_THROTTLE_KP)?,
+            "ki" => self.ki = value.parse_or(DEFAULT_AUTO_THROTTLE_KI)?,
+            "kd" => self.kd = value.parse_or(DEFAULT_AUTO_THROTTLE_KD)?,
+            "slew-rate" => self.slew_rate = value.parse_or(20)?,
+            "min-throttle" => self.min_throttle = core::cmp::min(value.parse_or(10)?, 100),
+            "max-throttle" => self.max_throttle = core::cmp::min(value.parse_or(100)?, 100),
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Breakpoint {
+    pub speed: u8,    // meters per second
+    pub throttle: u8, // percentage, used when speed not available
+    pub gain: u8,     // percentage of PID gains
+}
+
+impl PathSet for Breakpoint {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "speed" => self.speed = value.parse()?,
+            "throttle" => self.throttle = core::cmp::min(value.parse()?, 100),
+            "gain" => self.gain = value.parse()?,
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
+/// PID gain attenuation table, breakpoints in ascending order
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TPA(pub Vec<Breakpoint, 4>);
+
+impl PathSet for TPA {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        let index: usize = path.parse()?;
+        match index {
+            _ if index > self.0.len() => Err(Error::UnknownPath),
+            _ if index == self.0.len() => {
+                let breakpoint = Breakpoint { gain: 100, ..Default::default() };
+                self.0.push(breakpoint).map_err(|_| Error::UnknownPath)?;
+                self.0[index].set(path, value)
+            }
+            _ => self.0[index].set(path, value),
+        }
+    }
+}
+
+impl PathClear for TPA {
+    fn clear(&mut self, mut path: Path) -> Result<(), Error> {
+        match path.str() {
+            Ok(index) => {
+                let index: usize = index.parse().map_err(|_| Error::UnknownPath)?;
+                if index >= self.0.len() {
+                    return Err(Error::UnknownPath);
+                }
+                self.0.remove(index);
+            }
+            Err(_) => self.0.clear(),
+        }
+        Ok(())
+    }
+}
+
+const DEFAULT_ALTITUDE_HOLD_KP: FixedPoint<u16, 2> = fixed_point::fixed!(2.0, 2);
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AltitudeHold {
+    pub kp: FixedPoint<u16, 2>, // pitch degree per meter
+    pub max_pitch: u8,
+}
+
+impl Default for AltitudeHold {
+    fn default() -> Self {
+        Self { kp: DEFAULT_ALTITUDE_HOLD_KP, max_pitch: 15 }
+    }
+}
+
+impl PathSet for AltitudeHold {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "kp" => self.kp = value.parse_or(DEFAULT_ALTITUDE_HOLD_KP)?,
+            "max-pitch" => self.max_pitch = core::cmp::min(value.parse_or(15)?, 90),
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
+const DEFAULT_HEADING_HOLD_KP: FixedPoint<u16, 2> = fixed_point::fixed!(1.0, 2);
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct HeadingHold {
+    pub kp: FixedPoint<u16, 2>, // bank degree per heading degree
+    pub max_roll: u8,
+}
+
+impl Default for HeadingHold {
+    fn default() -> Self {
+        Self { kp: DEFAULT_HEADING_HOLD_KP, max_roll: 30 }
+    }
+}
+
+impl PathSet for HeadingHold {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "kp" => self.kp = value.parse_or(DEFAULT_HEADING_HOLD_KP)?,
+            "max-roll" => self.max_roll = core::cmp::min(value.parse_or(30)?, 90),
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
+/// Approach and landing on home along runway heading
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Autoland {
+    pub heading: u16,           // degree, runway heading
+    pub approach_distance: u16, // meters from landing point where glide slope begins
+    pub glide_slope: u8,        // degree
+    pub flare_height: u8,       // meters
+    pub flare_pitch: u8,        // degree
+    pub throttle: u8,           // percentage during approach
+}
+
+impl Default for Autoland {
+    fn default() -> Self {
+        Self {
+            heading: 0,
+            approach_distance: 300,
+            glide_slope: 5,
+            flare_height: 3,
+            flare_pitch: 5,
+            throttle: 40,
+        }
+    }
+}
+
+impl PathSet for Autoland {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "heading" => self.heading = value.parse_or(0)? % 360,
+            "approach-distance" => self.approach_distance = value.parse_or(300)?,
+            "glide-slope" => self.glide_slope = core::cmp::min(value.parse_or(5)?, 15),
+            "flare-height" => self.flare_height = value.parse_or(3)?,
+            "flare-pitch" => self.flare_pitch = core::cmp::min(value.parse_or(5)?, 15),
+            "throttle" => self.throttle = core::cmp::min(value.parse_or(40)?, 100),
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Direction {
+    Clockwise,
+    CounterClockwise,
+}
+
+impl FromStr for Direction {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, ()> {
+        match string {
+            "clockwise" => Ok(Self::Clockwise),
+            "counter-clockwise" => Ok(Self::CounterClockwise),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Loiter {
+    pub radius: u16, // meters
+    pub direction: Direction,
+    pub waypoint: u8, // mission waypoint number as center, 0 for position where engaged
+}
+
+impl Default for Loiter {
+    fn default() -> Self {
+        Self { radius: 100, direction: Direction::Clockwise, waypoint: 0 }
+    }
+}
+
+impl PathSet for Loiter {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "radius" => self.radius = core::cmp::max(value.parse_or(100)?, 10),
+            "direction" => self.direction = value.parse_or(Direction::Clockwise)?,
+            "waypoint" => self.waypoint = value.parse_or_default()?,
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Autopilot {
+    pub auto_throttle: AutoThrottle,
+    pub altitude_hold: AltitudeHold,
+    pub heading_hold: HeadingHold,
+    pub autoland: Autoland,
+    pub loiter: Loiter,
+}
+
+impl PathSet for Autopilot {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "auto-throttle" => self.auto_throttle.set(path, value),
+            "altitude-hold" => self.altitude_hold.set(path, value),
+            "heading-hold" => self.heading_hold.set(path, value),
+            "autoland" => self.autoland.set(path, value),
+            "loiter" => self.loiter.set(path, value),
+            _ => Err(Error::UnknownPath),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Mode {
+    Angle,
+    Rate,
+}
+
+impl FromStr for Mode {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, ()> {
+        match string {
+            "angle" => Ok(Self::Angle),
+            "rate" => Ok(Self::Rate),
+            _ => Err(()),
+        }
+    }
+}
+
+const DEFAULT_LEVEL_GAIN: FixedPoint<u16, 2> = fixed_point::fixed!(5.0, 2);
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Multirotor {
+    pub mode: Mode,
+    pub max_angle: u8,
+    pub level_gain: FixedPoint<u16, 2>, // degree per second per degree
+}
+
+impl Default for Multirotor {
+    fn default() -> Self {
+        Self { mode: Mode::Angle, max_angle: 30, level_gain: DEFAULT_LEVEL_GAIN }
+    }
+}
+
+impl PathSet for Multirotor {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "mode" => self.mode = value.parse_or(Mode::Angle)?,
+            "max-angle" => self.max_angle = core::cmp::min(value.parse_or(30)?, 90),
+            "level-gain" => self.level_gain = value.parse_or(DEFAULT_LEVEL_GAIN)?,
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct FCS {
     pub configuration: Configuration,
+    pub engines: Engines,
+    pub flaps: Flaps,
     pub gyroscope: Gyroscope,
     pub envelop: Envelop,
+    #[serde(rename = "turn-coordination")]
+    pub turn_coordination: TurnCoordination,
     pub pids: PIDs,
+    pub tpa: TPA,
+    pub autopilot: Autopilot,
+    pub launch: Launch,
+    pub multirotor: Multirotor,
+    pub mixer: Mixer,
 }
 
 impl Default for FCS {
     fn default() -> Self {
         Self {
             configuration: Configuration::Airplane,
+            engines: Default::default(),
+            flaps: Default::default(),
             gyroscope: Gyroscope::default(),
             envelop: Default::default(),
+            turn_coordination: Default::default(),
             pids: Default::default(),
+            tpa: Default::default(),
+            autopilot: Default::default(),
+            launch: Default::default(),
+            multirotor: Default::default(),
+            mixer: Default::default(),
         }
     }
 }
@@ -180,9 +742,27 @@ impl PathSet for FCS {
                 self.configuration = value.parse_or(Configuration::Airplane)?;
                 Ok(())
             }
+            "engines" => self.engines.set(path, value),
+            "flaps" => self.flaps.set(path, value),
             "gyroscope" => self.gyroscope.set(path, value),
             "envelop" => self.envelop.set(path, value),
+            "turn-coordination" => self.turn_coordination.set(path, value),
             "pids" => self.pids.set(path, value),
+            "tpa" => self.tpa.set(path, value),
+            "autopilot" => self.autopilot.set(path, value),
+            "launch" => self.launch.set(path, value),
+            "multirotor" => self.multirotor.set(path, value),
+            "mixer" => self.mixer.set(path, value),
+            _ => Err(Error::UnknownPath),
+        }
+    }
+}
+
+impl PathClear for FCS {
+    fn clear(&mut self, mut path: Path) -> Result<(), Error> {
+        match path.str()? {
+            "tpa" => self.tpa.clear(path),
+            "mixer" => self.mixer.clear(path),
             _ => Err(Error::UnknownPath),
         }
     }
@@ -9,9 +9,20 @@ use crate::{
 };
 
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct Axis {
     pub channel: u8,
     pub scale: FixedPoint<u8, 2>,
+    pub expo: u8,     // percentage
+    pub deadband: u8, // percentage of half stick travel
+    pub low_rate: u8, // percentage when low rate selected
+}
+
+impl Default for Axis {
+    fn default() -> Self {
+        let scale = fixed_point::fixed!(1.0);
+        Self { channel: u8::MAX, scale, expo: 0, deadband: 0, low_rate: 100 }
+    }
 }
 
 impl PathSet for Axis {
@@ -19,6 +30,9 @@ impl PathSet for Axis {
         match path.str()? {
             "channel" => self.channel = value.parse()?,
             "scale" => self.scale = value.parse_or(fixed_point::fixed!(1.0))?,
+            "expo" => self.expo = core::cmp::min(value.parse_or(0)?, 100),
+            "deadband" => self.deadband = core::cmp::min(value.parse_or(0)?, 50),
+            "low-rate" => self.low_rate = core::cmp::min(value.parse_or(100)?, 100),
             _ => return Err(Error::UnknownPath),
         }
         Ok(())
@@ -26,16 +40,15 @@ impl PathSet for Axis {
 }
 
 #[derive(Clone, Debug, PartialEq)]
-pub struct Axes(pub LinearMap<AxisType, Axis, 4>);
+pub struct Axes(pub LinearMap<AxisType, Axis, 9>);
 
 impl Default for Axes {
     fn default() -> Self {
         let mut axes = LinearMap::new();
-        let scale = fixed_point::fixed!(1.0);
-        axes.insert(AxisType::Throttle, Axis { channel: 3, scale }).ok();
-        axes.insert(AxisType::Roll, Axis { channel: 1, scale }).ok();
-        axes.insert(AxisType::Pitch, Axis { channel: 2, scale }).ok();
-        axes.insert(AxisType::Yaw, Axis { channel: 4, scale }).ok();
+        axes.insert(AxisType::Throttle, Axis { channel: 3, ..Default::default() }).ok();
+        axes.insert(AxisType::Roll, Axis { channel: 1, ..Default::default() }).ok();
+        axes.insert(AxisType::Pitch, Axis { channel: 2, ..Default::default() }).ok();
+        axes.insert(AxisType::Yaw, Axis { channel: 4, ..Default::default() }).ok();
         Self(axes)
     }
 }
@@ -46,7 +59,7 @@ impl PathSet for Axes {
         if self.0.contains_key(&axis_type) {
             return self.0[&axis_type].set(path, value);
         }
-        let mut config = Axis { channel: u8::MAX, scale: fixed_point::fixed!(1.0) };
+        let mut config = Axis::default();
         config.set(path, value)?;
         self.0.insert(axis_type, config).ok();
         Ok(())
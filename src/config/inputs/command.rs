@@ -6,10 +6,41 @@ pub enum Mode {
     Telemetry,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Autopilot {
+    Manual,
+    AutoThrottle,
+    AltitudeHold,
+    HeadingHold,
+    Navigation,
+    Autotune,
+    Autoland,
+    Loiter,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Arming {
+    Disarm,
+    Arm,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Rate {
+    #[serde(rename = "low-rate")]
+    Low,
+    #[serde(rename = "high-rate")]
+    High,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Id {
     Mode(Mode),
+    Autopilot(Autopilot),
+    Arming(Arming),
+    Rate(Rate),
 }
 
 impl core::str::FromStr for Id {
@@ -19,6 +50,18 @@ impl core::str::FromStr for Id {
         let id = match s {
             "nav-mode" => Self::Mode(Mode::NAV),
             "telemetry-mode" => Self::Mode(Mode::Telemetry),
+            "manual" => Self::Autopilot(Autopilot::Manual),
+            "auto-throttle" => Self::Autopilot(Autopilot::AutoThrottle),
+            "altitude-hold" => Self::Autopilot(Autopilot::AltitudeHold),
+            "heading-hold" => Self::Autopilot(Autopilot::HeadingHold),
+            "navigation" => Self::Autopilot(Autopilot::Navigation),
+            "autotune" => Self::Autopilot(Autopilot::Autotune),
+            "autoland" => Self::Autopilot(Autopilot::Autoland),
+            "loiter" => Self::Autopilot(Autopilot::Loiter),
+            "disarm" => Self::Arming(Arming::Disarm),
+            "arm" => Self::Arming(Arming::Arm),
+            "low-rate" => Self::Rate(Rate::Low),
+            "high-rate" => Self::Rate(Rate::High),
             _ => return Err(()),
         };
         Ok(id)
@@ -5,7 +5,7 @@ pub mod command;
 mod toggles;
 
 pub use axes::Axes;
-pub use toggles::Toggles;
+pub use toggles::{Toggle, Toggles};
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Inputs {
@@ -0,0 +1,122 @@
+use core::convert::TryFrom;
+
+use heapless::{String, Vec};
+
+use super::pathset::{Error, Path, PathClear, PathSet, Value};
+use crate::types::{
+    coordinate::{Latitude, Longitude, Position},
+    measurement::{unit::CentiMeter, Altitude, Distance},
+    waypoint::Waypoint,
+};
+
+impl PathSet for Position {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "latitude" => {
+                self.latitude = Latitude::try_from(value.str()?).map_err(|_| Error::InvalidValue)?
+            }
+            "longitude" => {
+                self.longitude =
+                    Longitude::try_from(value.str()?).map_err(|_| Error::InvalidValue)?
+            }
+            "altitude" => self.altitude = Altitude(Distance::new(value.parse()?, CentiMeter)),
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
+impl PathSet for Waypoint {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "name" => {
+                let mut name = String::new();
+                name.push_str(value.str()?).map_err(|_| Error::InvalidValue)?;
+                self.name = name;
+            }
+            "position" => self.position.set(path, value)?,
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Mission {
+    pub acceptance_radius: u16, // meters
+    pub waypoints: Vec<Waypoint, 16>,
+}
+
+impl Default for Mission {
+    fn default() -> Self {
+        Self { acceptance_radius: 50, waypoints: Vec::new() }
+    }
+}
+
+impl PathSet for Mission {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "acceptance-radius" => self.acceptance_radius = value.parse_or(50)?,
+            "waypoints" => {
+                let index: usize = path.parse()?;
+                match index {
+                    _ if index > self.waypoints.len() => return Err(Error::UnknownPath),
+                    _ if index == self.waypoints.len() => {
+                        let waypoint = Waypoint { name: String::new(), ..Default::default() };
+                        self.waypoints.push(waypoint).map_err(|_| Error::UnknownPath)?;
+                        self.waypoints[index].set(path, value)?
+                    }
+                    _ => self.waypoints[index].set(path, value)?,
+                }
+            }
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
+impl PathClear for Mission {
+    fn clear(&mut self, mut path: Path) -> Result<(), Error> {
+        match path.str()? {
+            "waypoints" => match path.str() {
+                Ok(index) => {
+                    let index: usize = index.parse().map_err(|_| Error::UnknownPath)?;
+                    if index >= self.waypoints.len() {
+                        return Err(Error::UnknownPath);
+                    }
+                    self.waypoints.remove(index);
+                }
+                Err(_) => self.waypoints.clear(),
+            },
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
+mod test {
+    #[test]
+    fn test_mission() {
+        use super::Mission;
+        use crate::config::pathset::{Path, PathClear, PathSet, Value};
+
+        let mut mission = Mission::default();
+        let set = |mission: &mut Mission, path: &'static str, value| {
+            mission.set(Path::new(path.split('.')), Value::of(value))
+        };
+        set(&mut mission, "waypoints.0.name", "WP1").unwrap();
+        set(&mut mission, "waypoints.0.position.latitude", "N22°32'432").unwrap();
+        set(&mut mission, "waypoints.1.name", "WP2").unwrap();
+        assert!(set(&mut mission, "waypoints.1.name", "WAYPOINT2").is_err());
+        assert!(set(&mut mission, "waypoints.3.name", "WP3").is_err());
+        assert_eq!(mission.waypoints.len(), 2);
+        assert_eq!(mission.waypoints[1].name.as_str(), "WP2");
+        assert_eq!(format!("{}", mission.waypoints[0].position.latitude), "N22°32'432");
+
+        mission.clear(Path::new("waypoints.0".split('.'))).unwrap();
+        assert_eq!(mission.waypoints[0].name.as_str(), "WP2");
+        mission.clear(Path::new("waypoints".split('.'))).unwrap();
+        assert!(mission.waypoints.is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+use core::{fmt::Write, str::FromStr};
+
+use heapless::Vec;
+use serde::de::Error as _;
+
+use super::{
+    pathset::{Error, Path, PathClear, PathSet, Value},
+    peripherals::pwm::ServoType,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Output {
+    Motor(u8),
+    Servo(ServoType),
+}
+
+impl FromStr for Output {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, ()> {
+        if let Some(index) = string.strip_prefix("motor-") {
+            return Ok(Self::Motor(index.parse().map_err(|_| ())?));
+        }
+        Ok(Self::Servo(string.parse()?))
+    }
+}
+
+impl serde::Serialize for Output {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Motor(index) => {
+                let mut s = heapless::String::<9>::new();
+                write!(s, "motor-{}", index).ok();
+                serializer.serialize_str(s.as_str())
+            }
+            Self::Servo(servo_type) => servo_type.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Output {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <&str>::deserialize(deserializer)?;
+        s.parse().map_err(|_| D::Error::custom("Malformed mixer output"))
+    }
+}
+
+/// Output value as weighted sum of inputs in percentage, limited to [min, max]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub output: Output,
+    pub roll: i8,
+    pub pitch: i8,
+    pub yaw: i8,
+    pub throttle: i8,
+    pub min: i8,
+    pub max: i8,
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self {
+            output: Output::Motor(0),
+            roll: 0,
+            pitch: 0,
+            yaw: 0,
+            throttle: 0,
+            min: -100,
+            max: 100,
+        }
+    }
+}
+
+fn percentage(value: Value, default: i8) -> Result<i8, Error> {
+    Ok(value.parse_or(default)?.clamp(-100, 100))
+}
+
+impl PathSet for Rule {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        match path.str()? {
+            "output" => self.output = value.parse()?,
+            "roll" => self.roll = percentage(value, 0)?,
+            "pitch" => self.pitch = percentage(value, 0)?,
+            "yaw" => self.yaw = percentage(value, 0)?,
+            "throttle" => self.throttle = percentage(value, 0)?,
+            "min" => self.min = percentage(value, -100)?,
+            "max" => self.max = percentage(value, 100)?,
+            _ => return Err(Error::UnknownPath),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Mixer(pub Vec<Rule, 8>);
+
+impl PathSet for Mixer {
+    fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
+        let index: usize = path.parse()?;
+        match index {
+            _ if index > self.0.len() => Err(Error::UnknownPath),
+            _ if index == self.0.len() => {
+                self.0.push(Rule::default()).map_err(|_| Error::UnknownPath)?;
+                self.0[index].set(path, value)
+            }
+            _ => self.0[index].set(path, value),
+        }
+    }
+}
+
+impl PathClear for Mixer {
+    fn clear(&mut self, mut path: Path) -> Result<(), Error> {
+        match path.str() {
+            Ok(index) => {
+                let index: usize = index.parse().map_err(|_| Error::UnknownPath)?;
+                if index >= self.0.len() {
+                    return Err(Error::UnknownPath);
+                }
+                self.0.remove(index);
+            }
+            Err(_) => self.0.clear(),
+        }
+        Ok(())
+    }
+}
+
+mod test {
+    #[test]
+    fn test_mixer() {
+        use super::Output;
+        use crate::config::{
+            FCS,
+            pathset::{Path, PathClear, PathSet, Value},
+            peripherals::pwm::ServoType,
+        };
+
+        let mut fcs = FCS::default();
+        let set = |fcs: &mut FCS, path: &'static str, value| {
+            fcs.set(Path::new(path.split('.')), Value::of(value))
+        };
+        set(&mut fcs, "mixer.0.output", "motor-1").unwrap();
+        set(&mut fcs, "mixer.0.throttle", "100").unwrap();
+        set(&mut fcs, "mixer.0.yaw", "-120").unwrap();
+        set(&mut fcs, "mixer.1.output", "elevator").unwrap();
+        assert!(set(&mut fcs, "mixer.1.output", "flap").is_err());
+        assert!(set(&mut fcs, "mixer.3.output", "rudder").is_err());
+        let mixer = &fcs.mixer;
+        assert_eq!(mixer.0.len(), 2);
+        assert_eq!(mixer.0[0].output, Output::Motor(1));
+        assert_eq!(mixer.0[0].yaw, -100);
+        assert_eq!(mixer.0[1].output, Output::Servo(ServoType::Elevator));
+
+        fcs.clear(Path::new("mixer.0".split('.'))).unwrap();
+        assert_eq!(fcs.mixer.0[0].output, Output::Servo(ServoType::Elevator));
+        fcs.clear(Path::new("mixer".split('.'))).unwrap();
+        assert!(fcs.mixer.0.is_empty());
+    }
+}
@@ -1,13 +1,18 @@
+pub mod arming;
 pub mod battery;
+pub mod failsafe;
 pub mod fcs;
 pub mod imu;
 pub mod inputs;
 pub mod ins;
+pub mod mission;
+pub mod mixer;
 pub mod osd;
 pub mod pathset;
 pub mod peripherals;
 pub mod yaml;
 
+use alloc::vec::Vec;
 use core::{mem, slice};
 
 use fixed_point::fixed;
@@ -16,11 +21,14 @@ use crate::{
     io::Read,
     types::sensor::{Bias, Gain},
 };
+pub use arming::Arming;
 pub use battery::Battery;
+pub use failsafe::Failsafe;
 pub use fcs::FCS;
 pub use imu::IMU;
 pub use inputs::Inputs;
 pub use ins::INS;
+pub use mission::Mission;
 pub use osd::{Offset, Standard, OSD};
 use pathset::{Error, Path, PathClear, PathSet, Value};
 pub use peripherals::{
@@ -70,6 +78,16 @@ pub struct Config {
     pub osd: OSD,
     pub peripherals: Peripherals,
     pub inputs: Inputs,
+    pub mission: Mission,
+    pub failsafe: Failsafe,
+    pub arming: Arming,
+}
+
+impl Config {
+    /// Checks settings depending on each other, which may be set in any order
+    pub fn validate(&self, timer_groups: &[&[&str]]) -> Result<(), Error> {
+        self.peripherals.pwms.validate(timer_groups)
+    }
 }
 
 impl PathSet for Config {
@@ -82,6 +100,9 @@ impl PathSet for Config {
             "osd" => self.osd.set(path, value),
             "peripherals" => self.peripherals.set(path, value),
             "inputs" => self.inputs.set(path, value),
+            "mission" => self.mission.set(path, value),
+            "failsafe" => self.failsafe.set(path, value),
+            "arming" => self.arming.set(path, value),
             _ => Err(Error::UnknownPath),
         }
     }
@@ -90,8 +111,10 @@ impl PathSet for Config {
 impl PathClear for Config {
     fn clear(&mut self, mut path: Path) -> Result<(), Error> {
         match path.str()? {
+            "fcs" => self.fcs.clear(path),
             "peripherals" => self.peripherals.clear(path),
             "inputs" => self.inputs.clear(path),
+            "mission" => self.mission.clear(path),
             _ => Err(Error::UnknownPath),
         }
     }
@@ -119,16 +142,36 @@ pub fn get() -> &'static Config {
 
 static mut CONFIG_ITERATION: usize = 1;
 
-pub fn load<E>(reader: &mut dyn Read<Error = E>) -> &'static Config {
-    let mut buffer = [0u8; 2048];
-    let size = reader.read(&mut buffer).ok().unwrap_or(0);
-    let config = if size > 0 {
-        YamlParser::new(unsafe { core::str::from_utf8_unchecked(&buffer[..size]) }).parse()
+/// Largest block heap allocator serves
+pub const MAX_FILE_SIZE: usize = 8192;
+
+#[derive(Debug, PartialEq)]
+pub enum LoadError<E> {
+    Read(E),
+    Oversize,
+}
+
+/// Reads until end of file, current config left untouched on error
+pub fn load<E>(reader: &mut dyn Read<Error = E>) -> Result<&'static Config, LoadError<E>> {
+    let mut buffer: Vec<u8> = Vec::with_capacity(MAX_FILE_SIZE);
+    let mut chunk = [0u8; 512];
+    loop {
+        let size = reader.read(&mut chunk).map_err(LoadError::Read)?;
+        if size == 0 {
+            break;
+        }
+        if buffer.len() + size > MAX_FILE_SIZE {
+            return Err(LoadError::Oversize);
+        }
+        buffer.extend_from_slice(&chunk[..size]);
+    }
+    let config = if !buffer.is_empty() {
+        YamlParser::new(unsafe { core::str::from_utf8_unchecked(&buffer) }).parse()
     } else {
         Config::default()
     };
     unsafe { CONFIG = Some(config) }
-    get()
+    Ok(get())
 }
 
 pub fn replace(config: &Config) {
@@ -179,6 +222,36 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    #[serial]
+    fn test_load() {
+        use std::fs;
+
+        use super::{load, LoadError, MAX_FILE_SIZE};
+        use crate::io::Read;
+
+        // Yields at most 100 bytes each read
+        struct Reader<'a>(&'a [u8]);
+
+        impl<'a> Read for Reader<'a> {
+            type Error = ();
+
+            fn read(&mut self, buf: &mut [u8]) -> Result<usize, ()> {
+                let size = [buf.len(), self.0.len(), 100].into_iter().min().unwrap();
+                buf[..size].copy_from_slice(&self.0[..size]);
+                self.0 = &self.0[size..];
+                Ok(size)
+            }
+        }
+
+        let yaml = fs::read("sample.yml").unwrap();
+        let config = load(&mut Reader(&yaml)).unwrap();
+        assert_eq!(config.fcs.pids.roll.max_rate, 150);
+
+        let oversize = [b' '; MAX_FILE_SIZE + 1];
+        assert_eq!(load(&mut Reader(&oversize)).err(), Some(LoadError::Oversize));
+    }
+
     #[test]
     #[serial]
     fn test_binary_decode() -> std::io::Result<()> {
@@ -5,6 +5,7 @@ pub enum Error {
     UnknownPath,
     ExpectValue,
     InvalidValue,
+    Conflict,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -47,6 +48,7 @@ impl core::fmt::Display for Error {
             Self::UnknownPath => "Specified path not exists or invalid",
             Self::ExpectValue => "Expect value",
             Self::InvalidValue => "Value not valid",
+            Self::Conflict => "Value conflicts with other settings",
         };
         write!(f, "{}", err_string)
     }
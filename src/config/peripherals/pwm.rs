@@ -1,6 +1,6 @@
 use core::{cmp, fmt::Write, str::FromStr};
 
-use heapless::LinearMap;
+use heapless::{LinearMap, Vec};
 use serde::{de::Error as _, ser::SerializeMap};
 
 use crate::{
@@ -50,6 +50,57 @@ impl<'de> serde::Deserialize<'de> for Identifier {
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Protocol {
     PWM,
+    OneShot125,
+    OneShot42,
+    MultiShot,
+    DShot150,
+    DShot300,
+    DShot600,
+}
+
+impl Protocol {
+    /// Minimum and maximum pulse width in nanoseconds of analog protocol
+    pub fn pulse_width(self) -> Option<(u32, u32)> {
+        match self {
+            Self::PWM => Some((1_000_000, 2_000_000)),
+            Self::OneShot125 => Some((125_000, 250_000)),
+            Self::OneShot42 => Some((42_000, 84_000)),
+            Self::MultiShot => Some((5_000, 25_000)),
+            _ => None,
+        }
+    }
+
+    /// Highest frame rate leaving room for maximum pulse width of analog protocol
+    pub fn max_rate(self) -> Option<u16> {
+        self.pulse_width().map(|(_, max)| cmp::min(1_000_000_000 / max - 1, u16::MAX as u32) as u16)
+    }
+
+    /// Bit rate in bit per second of digital protocol
+    pub fn bitrate(self) -> Option<u32> {
+        match self {
+            Self::PWM | Self::OneShot125 | Self::OneShot42 | Self::MultiShot => None,
+            Self::DShot150 => Some(150_000),
+            Self::DShot300 => Some(300_000),
+            Self::DShot600 => Some(600_000),
+        }
+    }
+}
+
+impl FromStr for Protocol {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, ()> {
+        match string {
+            "PWM" => Ok(Self::PWM),
+            "OneShot125" => Ok(Self::OneShot125),
+            "OneShot42" => Ok(Self::OneShot42),
+            "MultiShot" => Ok(Self::MultiShot),
+            "DShot150" => Ok(Self::DShot150),
+            "DShot300" => Ok(Self::DShot300),
+            "DShot600" => Ok(Self::DShot600),
+            _ => Err(()),
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -59,17 +110,20 @@ pub struct Motor {
     pub index: u8,
     pub protocol: Protocol,
     pub rate: u16,
+    /// Bidirectional DShot replying eRPM, converted to RPM by number of motor poles
+    pub bidirectional: bool,
+    pub poles: u8,
 }
 
 impl Motor {
     pub fn new(protocol: Protocol, index: u8, rate: u16) -> Self {
-        Self { protocol, index, rate }
+        Self { protocol, index, rate, ..Default::default() }
     }
 }
 
 impl Default for Motor {
     fn default() -> Self {
-        Self { protocol: Protocol::PWM, index: 0, rate: 400 }
+        Self { protocol: Protocol::PWM, index: 0, rate: 400, bidirectional: false, poles: 14 }
     }
 }
 
@@ -84,6 +138,40 @@ pub enum ServoType {
     Rudder,
     RuddervatorLeft,
     RuddervatorRight,
+    FlapLeft,
+    FlapRight,
+    FlaperonLeft,
+    FlaperonRight,
+    Aux1,
+    Aux2,
+    Aux3,
+    Aux4,
+}
+
+impl FromStr for ServoType {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, ()> {
+        match string {
+            "aileron-left" => Ok(Self::AileronLeft),
+            "aileron-right" => Ok(Self::AileronRight),
+            "elevator" => Ok(Self::Elevator),
+            "elevon-left" => Ok(Self::ElevonLeft),
+            "elevon-right" => Ok(Self::ElevonRight),
+            "rudder" => Ok(Self::Rudder),
+            "ruddervator-left" => Ok(Self::RuddervatorLeft),
+            "ruddervator-right" => Ok(Self::RuddervatorRight),
+            "flap-left" => Ok(Self::FlapLeft),
+            "flap-right" => Ok(Self::FlapRight),
+            "flaperon-left" => Ok(Self::FlaperonLeft),
+            "flaperon-right" => Ok(Self::FlaperonRight),
+            "aux1" => Ok(Self::Aux1),
+            "aux2" => Ok(Self::Aux2),
+            "aux3" => Ok(Self::Aux3),
+            "aux4" => Ok(Self::Aux4),
+            _ => Err(()),
+        }
+    }
 }
 
 impl Into<&str> for ServoType {
@@ -97,11 +185,24 @@ impl Into<&str> for ServoType {
             Self::Rudder => "rudder",
             Self::RuddervatorLeft => "ruddervator-left",
             Self::RuddervatorRight => "ruddervator-right",
+            Self::FlapLeft => "flap-left",
+            Self::FlapRight => "flap-right",
+            Self::FlaperonLeft => "flaperon-left",
+            Self::FlaperonRight => "flaperon-right",
+            Self::Aux1 => "aux1",
+            Self::Aux2 => "aux2",
+            Self::Aux3 => "aux3",
+            Self::Aux4 => "aux4",
         }
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Output curve points in percentage of travel, evenly spaced over input range
+pub type Curve = Vec<i8, 9>;
+
+/// Pulse endpoints, subtrim in microseconds, slew rate in degree per second,
+/// frame rate in Hz where 50 for analog servo and 200 or 333 for digital servo
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct Servo {
     #[serde(rename = "type")]
@@ -109,19 +210,37 @@ pub struct Servo {
     pub min_angle: i8,
     pub max_angle: i8,
     pub reversed: bool,
+    pub subtrim: i16,
+    pub min_pulse: u16,
+    pub max_pulse: u16,
+    pub slew_rate: u16,
+    pub rate: u16,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub curve: Curve,
 }
 
 impl Servo {
     pub fn new(servo_type: ServoType, min_angle: i8, max_angle: i8, reversed: bool) -> Self {
-        Self { servo_type, min_angle, max_angle, reversed }
+        Self { servo_type, min_angle, max_angle, reversed, ..Self::of(servo_type) }
     }
 
     pub fn of(servo_type: ServoType) -> Self {
-        Self { servo_type, min_angle: -90, max_angle: 90, reversed: false }
+        Self {
+            servo_type,
+            min_angle: -90,
+            max_angle: 90,
+            reversed: false,
+            subtrim: 0,
+            min_pulse: 500,
+            max_pulse: 2500,
+            slew_rate: 0,
+            rate: 50,
+            curve: Vec::new(),
+        }
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(untagged)]
 pub enum PWM {
@@ -130,10 +249,28 @@ pub enum PWM {
 }
 
 impl PWM {
-    pub fn rate(self) -> u16 {
+    pub fn rate(&self) -> u16 {
         match self {
             Self::Motor(motor) => motor.rate,
-            _ => 50,
+            Self::Servo(servo) => servo.rate,
+        }
+    }
+
+    /// Frame of analog motor protocol shall leave room for maximum pulse width
+    pub fn validate(&self) -> Result<(), Error> {
+        match self {
+            Self::Motor(m) if m.rate > m.protocol.max_rate().unwrap_or(u16::MAX) => {
+                Err(Error::Conflict)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Frequency of timer driving this output, bit rate for digital protocol
+    pub fn timer_rate(&self) -> u32 {
+        match self {
+            Self::Motor(motor) => motor.protocol.bitrate().unwrap_or(motor.rate as u32),
+            Self::Servo(servo) => servo.rate as u32,
         }
     }
 }
@@ -142,30 +279,19 @@ impl PathSet for PWM {
     fn set(&mut self, mut path: Path, value: Value) -> Result<(), Error> {
         let key = path.str()?;
         if key == "type" {
-            let output_type = value.str()?;
-            *self = match output_type {
+            *self = match value.str()? {
                 "motor" => Self::Motor(Motor::default()),
-                "aileron-left" => Self::Servo(Servo::of(ServoType::AileronLeft)),
-                "aileron-right" => Self::Servo(Servo::of(ServoType::AileronRight)),
-                "elevator" => Self::Servo(Servo::of(ServoType::Elevator)),
-                "rudder" => Self::Servo(Servo::of(ServoType::Rudder)),
-                "elevon-left" => Self::Servo(Servo::of(ServoType::ElevonLeft)),
-                "elevon-right" => Self::Servo(Servo::of(ServoType::ElevonRight)),
-                "ruddervator-left" => Self::Servo(Servo::of(ServoType::RuddervatorLeft)),
-                "ruddervator-right" => Self::Servo(Servo::of(ServoType::RuddervatorRight)),
-                _ => return Err(Error::InvalidValue),
+                _ => Self::Servo(Servo::of(value.parse()?)),
             };
             return Ok(());
         }
         match self {
             Self::Motor(ref mut motor) => match key {
                 "index" => motor.index = value.parse()?,
-                "protocol" => match value.0 {
-                    Some("PWM") => motor.protocol = Protocol::PWM,
-                    Some(_) => return Err(Error::InvalidValue),
-                    _ => motor.protocol = Protocol::PWM,
-                },
+                "protocol" => motor.protocol = value.parse_or(Protocol::PWM)?,
                 "rate" => motor.rate = value.parse_or(400)?,
+                "bidirectional" => motor.bidirectional = value.parse_or(false)?,
+                "poles" => motor.poles = cmp::max(value.parse_or(14)?, 2),
                 _ => return Err(Error::UnknownPath),
             },
             Self::Servo(ref mut servo) => match key {
@@ -178,6 +304,29 @@ impl PathSet for PWM {
                     servo.max_angle = cmp::max(cmp::min(max, 90), 0)
                 }
                 "reversed" => servo.reversed = value.parse()?,
+                "subtrim" => servo.subtrim = value.parse_or(0i16)?.clamp(-500, 500),
+                "min-pulse" => servo.min_pulse = value.parse_or(500u16)?.clamp(500, 1500),
+                "max-pulse" => servo.max_pulse = value.parse_or(2500u16)?.clamp(1500, 2500),
+                "slew-rate" => servo.slew_rate = value.parse_or_default()?,
+                "rate" => match value.parse_or(50)? {
+                    rate @ (50 | 200 | 333) => servo.rate = rate,
+                    _ => return Err(Error::InvalidValue),
+                },
+                "curve" => {
+                    if value.0.is_none() {
+                        servo.curve.clear();
+                        return Ok(());
+                    }
+                    let index: usize = path.parse()?;
+                    let point = value.parse::<i8>()?.clamp(-100, 100);
+                    match index {
+                        _ if index < servo.curve.len() => servo.curve[index] = point,
+                        _ if index == servo.curve.len() => {
+                            servo.curve.push(point).map_err(|_| Error::UnknownPath)?;
+                        }
+                        _ => return Err(Error::UnknownPath),
+                    }
+                }
                 _ => return Err(Error::UnknownPath),
             },
         }
@@ -185,6 +334,13 @@ impl PathSet for PWM {
     }
 }
 
+fn agree<T: PartialEq>(mut values: impl Iterator<Item = T>) -> bool {
+    match values.next() {
+        Some(first) => values.all(|value| value == first),
+        None => true,
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct PWMs(pub LinearMap<Identifier, PWM, 8>);
 
@@ -192,6 +348,45 @@ impl PWMs {
     pub fn get(&self, name: &str) -> Option<&PWM> {
         Identifier::from_str(name).ok().map(|id| self.0.get(&id)).flatten()
     }
+
+    /// Highest servo frame rate, servo outputs should be refreshed at least this often
+    pub fn servo_rate(&self) -> u16 {
+        let servos = self.0.values().filter(|pwm| matches!(pwm, PWM::Servo(_)));
+        servos.map(|pwm| pwm.rate()).max().unwrap_or(50)
+    }
+
+    /// Highest motor rate, for DShot it is how often frames are sent since
+    /// timer runs at protocol bitrate
+    pub fn motor_rate(&self) -> u16 {
+        let motors = self.0.values().filter(|pwm| matches!(pwm, PWM::Motor(_)));
+        motors.map(|pwm| pwm.rate()).max().unwrap_or(50)
+    }
+
+    /// Configured outputs sharing a timer shall agree on timer rate, protocol family
+    /// and bidirectional DShot, since the timer is reloaded for eRPM reply,
+    /// servo shares timer with standard PWM motor only
+    pub fn validate_group(&self, group: &[&str]) -> Result<(), Error> {
+        let outputs = || group.iter().filter_map(|name| self.get(name));
+        outputs().try_for_each(PWM::validate)?;
+        let dshot = |pwm: &PWM| matches!(pwm, PWM::Motor(m) if m.protocol.bitrate().is_some());
+        let bidirectional = |pwm: &PWM| matches!(pwm, PWM::Motor(m) if m.bidirectional);
+        let servo = |pwm: &PWM| matches!(pwm, PWM::Servo(_));
+        let esc = |pwm: &PWM| matches!(pwm, PWM::Motor(m) if m.protocol != Protocol::PWM);
+        let agreed = agree(outputs().map(|pwm| pwm.timer_rate()))
+            && agree(outputs().map(dshot))
+            && agree(outputs().map(bidirectional));
+        if !agreed || (outputs().any(servo) && outputs().any(esc)) {
+            return Err(Error::Conflict);
+        }
+        Ok(())
+    }
+
+    /// Checks settings depending on each other once all of them set,
+    /// timer groups list outputs driven by one timer, as wired by board
+    pub fn validate(&self, timer_groups: &[&[&str]]) -> Result<(), Error> {
+        self.0.values().try_for_each(PWM::validate)?;
+        timer_groups.iter().try_for_each(|group| self.validate_group(group))
+    }
 }
 
 impl serde::Serialize for PWMs {
@@ -230,3 +425,70 @@ impl PathClear for PWMs {
         Ok(())
     }
 }
+
+mod test {
+    #[test]
+    fn test_timer_group() {
+        use super::PWMs;
+        use crate::config::pathset::{Error, Path, PathSet, Value};
+
+        let mut pwms = PWMs::default();
+        let set = |pwms: &mut PWMs, path: &'static str, value| {
+            pwms.set(Path::new(path.split('.')), Value::of(value))
+        };
+        set(&mut pwms, "PWM1.type", "motor").unwrap();
+        set(&mut pwms, "PWM1.rate", "50").unwrap();
+        set(&mut pwms, "PWM2.type", "elevator").unwrap();
+        set(&mut pwms, "PWM3.type", "rudder").unwrap();
+        set(&mut pwms, "PWM3.rate", "333").unwrap();
+        assert_eq!(pwms.validate_group(&["PWM1", "PWM2"]), Ok(()));
+        assert_eq!(pwms.validate_group(&["PWM3", "PWM4"]), Ok(()));
+        assert_eq!(pwms.validate_group(&["PWM2", "PWM3"]), Err(Error::Conflict));
+
+        // Should reject DShot outputs differing in bit rate or bidirectional
+        set(&mut pwms, "PWM5.protocol", "DShot600").unwrap();
+        set(&mut pwms, "PWM6.protocol", "DShot300").unwrap();
+        assert_eq!(pwms.validate_group(&["PWM5", "PWM6"]), Err(Error::Conflict));
+        set(&mut pwms, "PWM6.protocol", "DShot600").unwrap();
+        assert_eq!(pwms.validate_group(&["PWM5", "PWM6"]), Ok(()));
+        set(&mut pwms, "PWM6.bidirectional", "true").unwrap();
+        assert_eq!(pwms.validate_group(&["PWM5", "PWM6"]), Err(Error::Conflict));
+
+        // Should reject analog ESC protocol sharing timer with servo even at same rate
+        set(&mut pwms, "PWM1.protocol", "OneShot125").unwrap();
+        assert_eq!(pwms.validate_group(&["PWM1", "PWM2"]), Err(Error::Conflict));
+        assert_eq!(pwms.validate(&[&["PWM1", "PWM2"]]), Err(Error::Conflict));
+    }
+
+    #[test]
+    fn test_motor_rate() {
+        use super::PWMs;
+        use crate::config::pathset::{Error, Path, PathSet, Value};
+
+        let set = |pwms: &mut PWMs, path: &'static str, value| {
+            pwms.set(Path::new(path.split('.')), Value::of(value))
+        };
+        // Should accept rate and protocol in any order
+        let mut pwms = PWMs::default();
+        set(&mut pwms, "PWM1.rate", "2000").unwrap();
+        set(&mut pwms, "PWM1.protocol", "OneShot125").unwrap();
+        assert_eq!(pwms.validate(&[]), Ok(()));
+        let mut reordered = PWMs::default();
+        set(&mut reordered, "PWM1.protocol", "OneShot125").unwrap();
+        set(&mut reordered, "PWM1.rate", "2000").unwrap();
+        assert_eq!(reordered, pwms);
+
+        // Should reject rate leaving no room for maximum pulse width
+        set(&mut pwms, "PWM1.protocol", "PWM").unwrap();
+        assert_eq!(pwms.validate(&[]), Err(Error::Conflict));
+        set(&mut pwms, "PWM1.rate", "400").unwrap();
+        assert_eq!(pwms.validate(&[]), Ok(()));
+
+        // Should schedule motors at highest motor rate regardless of servos
+        set(&mut pwms, "PWM2.protocol", "DShot600").unwrap();
+        set(&mut pwms, "PWM2.rate", "1000").unwrap();
+        set(&mut pwms, "PWM3.type", "aileron-left").unwrap();
+        assert_eq!(pwms.motor_rate(), 1000);
+        assert_eq!(pwms.servo_rate(), 50);
+    }
+}
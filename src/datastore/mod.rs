@@ -6,14 +6,16 @@ use nalgebra::Vector3;
 
 use crate::{
     fcs::out::FCS,
-    imu::out::IMU,
+    imu::{out::IMU, CalibrationStatus},
     ins::out::INS,
     protocol::serial::gnss::out::GNSS,
+    servo::out::MotorRPM,
     sync::ReadSpinLock,
     sys::jiffies,
     types::{
         control::Control,
         measurement::{voltage::Voltage, Altitude},
+        waypoint::Steerpoint,
     },
 };
 
@@ -34,8 +36,10 @@ impl<T: Clone + Default> Entry<T> {
         self.data.clone().unwrap_or_default()
     }
 
-    fn read_within(&self, max_timestamp: Duration) -> Option<T> {
-        if self.timestamp > max_timestamp {
+    /// Data written no longer than timeout ago, IMU and INS pass their sample interval
+    /// so that each sample of slower sensors gets fused once
+    fn read_within(&self, now: Duration, timeout: Duration) -> Option<T> {
+        if self.timestamp + timeout < now {
             return None;
         }
         self.data.clone()
@@ -53,7 +57,7 @@ macro_rules! datastore {
             $(
                 concat_idents!(getter = read_, $names, _within {
                     pub fn getter(&self, timeout: Duration) -> Option<$types> {
-                        self.$names.read().read_within(jiffies::get() + timeout.convert())
+                        self.$names.read().read_within(jiffies::get(), timeout)
                     }
                 });
 
@@ -78,12 +82,15 @@ macro_rules! datastore {
 
 datastore! {
     baro_altitude: Altitude,
+    calibration: CalibrationStatus,
     control: Control,
     fcs: FCS,
     gnss: GNSS,
     imu: IMU,
     ins: INS,
     magnetism: Vector3<f32>,
+    motor_rpm: MotorRPM,
+    steerpoint: Steerpoint,
     voltage: Voltage
 }
 
@@ -98,3 +105,19 @@ pub fn acquire() -> &'static DataStore {
 pub fn init() {
     *unsafe { &mut *DATASTORE.as_mut_ptr() } = Default::default()
 }
+
+mod test {
+    #[test]
+    fn test_read_within() {
+        use fugit::NanosDurationU64 as Duration;
+
+        use super::Entry;
+
+        let entry = Entry { timestamp: Duration::millis(1000), data: Some(1u8) };
+        assert_eq!(entry.read_within(Duration::millis(1010), Duration::millis(20)), Some(1));
+        assert_eq!(entry.read_within(Duration::millis(1020), Duration::millis(20)), Some(1));
+        assert_eq!(entry.read_within(Duration::millis(1030), Duration::millis(20)), None);
+        let entry = Entry::<u8> { timestamp: Duration::millis(1000), data: None };
+        assert_eq!(entry.read_within(Duration::millis(1000), Duration::millis(20)), None);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::{
+    config::{
+        inputs::command::{Arming as Command, Id},
+        Config,
+    },
+    imu::CalibrationStatus,
+    types::{control::Control, measurement::voltage::Voltage},
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Check {
+    Calibration,
+    Throttle,
+    Link,
+    Battery,
+    GNSS,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum State {
+    Blocked(Check),
+    #[default]
+    Disarmed,
+    Armed,
+}
+
+pub struct Sensors {
+    pub calibration: CalibrationStatus,
+    pub voltage: Voltage,
+    pub gnss_fixed: bool,
+}
+
+const MAX_ARMING_THROTTLE: u16 = u16::MAX / 20;
+
+pub struct Arming {
+    require_gnss_fix: bool,
+    min_voltage: Voltage,
+    auto_arm: bool,
+    arm_command: bool,
+    armed: bool,
+    state: State,
+}
+
+impl Arming {
+    pub fn new(config: &Config) -> Self {
+        let mut arming = Self {
+            require_gnss_fix: false,
+            min_voltage: Default::default(),
+            auto_arm: false,
+            arm_command: true, // arm switch must be released after power up
+            armed: false,
+            state: State::default(),
+        };
+        arming.reconfigure(config);
+        arming
+    }
+
+    pub fn reconfigure(&mut self, config: &Config) {
+        let battery = &config.battery;
+        self.require_gnss_fix = config.arming.require_gnss_fix;
+        self.min_voltage = Voltage::from(battery.warning_cell_voltage.0.0 * battery.cells as u16);
+        let arm = Id::Arming(Command::Arm);
+        let arm_toggle = config.inputs.toggles.0.iter().any(|t| t.choices.contains(&arm));
+        self.auto_arm = config.arming.auto_arm && !arm_toggle;
+        if !arm_toggle && !config.arming.auto_arm {
+            warn!("Neither arm toggle nor auto-arm configured, motors stay disarmed");
+        }
+    }
+
+    /// Returns first failed pre-arm check
+    pub fn check(&self, control: Option<&Control>, sensors: &Sensors) -> Option<Check> {
+        if sensors.calibration != CalibrationStatus::Calibrated {
+            return Some(Check::Calibration);
+        }
+        let control = match control {
+            Some(control) if !control.failsafe => control,
+            _ => return Some(Check::Link),
+        };
+        if control.axes.throttle > MAX_ARMING_THROTTLE {
+            return Some(Check::Throttle);
+        }
+        if sensors.voltage.0.0 < self.min_voltage.0.0 {
+            return Some(Check::Battery);
+        }
+        if self.require_gnss_fix && !sensors.gnss_fixed {
+            return Some(Check::GNSS);
+        }
+        None
+    }
+
+    fn next_state(&mut self, control: Option<&Control>, sensors: &Sensors) -> State {
+        let arm_command = control.map(|c| c.contains(Id::Arming(Command::Arm))).unwrap_or(false);
+        let arm_requested = self.auto_arm || (arm_command && !self.arm_command);
+        self.arm_command = arm_command;
+        if self.armed {
+            if control.map(|c| c.contains(Id::Arming(Command::Disarm))).unwrap_or(false) {
+                self.armed = false;
+            }
+        } else if let Some(check) = self.check(control, sensors) {
+            return State::Blocked(check);
+        } else if arm_requested {
+            self.armed = true;
+        }
+        if self.armed { State::Armed } else { State::Disarmed }
+    }
+
+    /// Arms on arm command, or automatically if opted in without arm toggle,
+    /// once armed only disarm command disarms
+    pub fn update(&mut self, control: Option<&Control>, sensors: &Sensors) -> State {
+        let state = self.next_state(control, sensors);
+        if state != self.state {
+            match state {
+                State::Blocked(check) => warn!("Arming blocked by {:?} check", check),
+                _ => info!("{:?}", state),
+            }
+            self.state = state;
+        }
+        state
+    }
+}
+
+mod test {
+    #[test]
+    fn test_arming_checks() {
+        use super::{Arming, Check, Sensors, State};
+        use crate::{
+            config::Config,
+            imu::CalibrationStatus,
+            types::{control::Control, measurement::voltage::Voltage},
+        };
+
+        let mut config = Config::default();
+        config.arming.require_gnss_fix = true;
+        config.arming.auto_arm = true;
+        let mut arming = Arming::new(&config);
+        let mut sensors = Sensors {
+            calibration: CalibrationStatus::Calibrating,
+            voltage: voltage!(11.1),
+            gnss_fixed: false,
+        };
+        let mut control = Control::default();
+        assert_eq!(arming.update(Some(&control), &sensors), State::Blocked(Check::Calibration));
+        sensors.calibration = CalibrationStatus::Calibrated;
+        assert_eq!(arming.update(None, &sensors), State::Blocked(Check::Link));
+        control.axes.throttle = u16::MAX / 2;
+        assert_eq!(arming.update(Some(&control), &sensors), State::Blocked(Check::Throttle));
+        control.axes.throttle = 0;
+        sensors.voltage = voltage!(10.2);
+        assert_eq!(arming.update(Some(&control), &sensors), State::Blocked(Check::Battery));
+        sensors.voltage = voltage!(11.1);
+        assert_eq!(arming.update(Some(&control), &sensors), State::Blocked(Check::GNSS));
+        sensors.gnss_fixed = true;
+        assert_eq!(arming.update(Some(&control), &sensors), State::Armed);
+
+        // Should stay disarmed without arm toggle unless auto arm opted in
+        let mut arming = Arming::new(&Config::default());
+        assert_eq!(arming.update(Some(&control), &sensors), State::Disarmed);
+    }
+
+    #[test]
+    fn test_arming_toggle() {
+        use super::{Arming, Sensors, State};
+        use crate::{
+            config::{
+                inputs::{
+                    command::{Arming as Command, Id},
+                    Toggle,
+                },
+                Config,
+            },
+            imu::CalibrationStatus,
+            types::{control::Control, measurement::voltage::Voltage},
+        };
+
+        let mut config = Config::default();
+        let mut toggle = Toggle::default();
+        toggle.choices.push(Id::Arming(Command::Disarm)).ok();
+        toggle.choices.push(Id::Arming(Command::Arm)).ok();
+        config.inputs.toggles.0.push(toggle).ok();
+        let mut arming = Arming::new(&config);
+        let sensors = Sensors {
+            calibration: CalibrationStatus::Calibrated,
+            voltage: voltage!(11.1),
+            gnss_fixed: false,
+        };
+
+        // Should not arm when arm switch on at power up
+        let mut control = Control::default();
+        control.commands.push(Id::Arming(Command::Arm)).ok();
+        assert_eq!(arming.update(Some(&control), &sensors), State::Disarmed);
+
+        let mut disarm = Control::default();
+        disarm.commands.push(Id::Arming(Command::Disarm)).ok();
+        assert_eq!(arming.update(Some(&disarm), &sensors), State::Disarmed);
+        assert_eq!(arming.update(Some(&control), &sensors), State::Armed);
+
+        // Should keep armed on link loss or throttle up
+        assert_eq!(arming.update(None, &sensors), State::Armed);
+        control.axes.throttle = u16::MAX;
+        assert_eq!(arming.update(Some(&control), &sensors), State::Armed);
+        assert_eq!(arming.update(Some(&disarm), &sensors), State::Disarmed);
+    }
+}
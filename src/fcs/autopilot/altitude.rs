@@ -0,0 +1,57 @@
+use crate::config::fcs::AltitudeHold as Config;
+
+pub struct AltitudeHold {
+    config: Config,
+    target: Option<f32>,
+}
+
+impl AltitudeHold {
+    pub fn new(config: &Config) -> Self {
+        Self { config: *config, target: None }
+    }
+
+    pub fn reconfigure(&mut self, config: &Config) {
+        self.config = *config;
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.target.is_some()
+    }
+
+    pub fn disengage(&mut self) {
+        self.target = None;
+    }
+
+    /// Altitude in meters, overrides captured altitude
+    pub fn set_target(&mut self, altitude: f32) {
+        self.target = Some(altitude);
+    }
+
+    /// Altitude in meters, returns pitch angle in degrees,
+    /// current altitude will be held once engaged
+    pub fn update(&mut self, altitude: f32) -> f32 {
+        let target = *self.target.get_or_insert(altitude);
+        let kp: f32 = self.config.kp.into();
+        let max_pitch = self.config.max_pitch as f32;
+        ((target - altitude) * kp).clamp(-max_pitch, max_pitch)
+    }
+}
+
+mod test {
+    #[test]
+    fn test_altitude_hold() {
+        use super::AltitudeHold;
+        use crate::config::fcs::AltitudeHold as Config;
+
+        let mut altitude_hold = AltitudeHold::new(&Config::default());
+        assert_eq!(altitude_hold.update(100.0), 0.0);
+        assert!(altitude_hold.is_engaged());
+
+        assert_eq!(altitude_hold.update(98.0), 4.0);
+        assert_eq!(altitude_hold.update(101.0), -2.0);
+        assert_eq!(altitude_hold.update(0.0), 15.0);
+
+        altitude_hold.disengage();
+        assert_eq!(altitude_hold.update(0.0), 0.0);
+    }
+}
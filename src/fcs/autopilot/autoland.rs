@@ -0,0 +1,149 @@
+#[cfg(not(any(test, feature = "std")))]
+use micromath::F32Ext;
+
+use crate::{config::fcs::Autoland as Config, ins::out::INS};
+
+const APPROACH_RADIUS: f32 = 50.0; // meters around approach point
+const CROSS_TRACK_GAIN: f32 = 1.0; // degree of heading correction per meter
+const MAX_CORRECTION: f32 = 30.0; // degree
+const LANDED_SPEED: f32 = 3.0; // meters per second
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Phase {
+    Approach,
+    Glide,
+    Flare,
+    Landed,
+}
+
+/// Heading in degrees, altitude in meters relative to home,
+/// pitch in degrees overrides altitude when specified
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Target {
+    pub heading: f32,
+    pub altitude: f32,
+    pub pitch: Option<f32>,
+    pub throttle: u16,
+}
+
+/// Lands on home, approach point lies before home opposite to runway heading
+pub struct Autoland {
+    config: Config,
+    phase: Option<Phase>,
+}
+
+fn azimuth(x: f32, y: f32) -> f32 {
+    let azimuth = x.atan2(y).to_degrees();
+    if azimuth < 0.0 { azimuth + 360.0 } else { azimuth }
+}
+
+impl Autoland {
+    pub fn new(config: &Config) -> Self {
+        Self { config: *config, phase: None }
+    }
+
+    pub fn reconfigure(&mut self, config: &Config) {
+        self.config = *config;
+    }
+
+    pub fn phase(&self) -> Option<Phase> {
+        self.phase
+    }
+
+    pub fn disengage(&mut self) {
+        self.phase = None;
+    }
+
+    pub fn update(&mut self, ins: &INS) -> Target {
+        let heading = self.config.heading as f32;
+        let (sin, cos) = (heading.to_radians().sin(), heading.to_radians().cos());
+        let (x, y) = (ins.displacement.x().raw, ins.displacement.y().raw);
+        let height = ins.displacement.z().raw;
+        let along = x * sin + y * cos; // negative before landing point
+        let cross = x * cos - y * sin; // positive at right side of runway
+        let distance = self.config.approach_distance as f32;
+        let slope = (self.config.glide_slope as f32).to_radians().tan();
+        let throttle = (self.config.throttle as u32 * u16::MAX as u32 / 100) as u16;
+
+        let phase = match self.phase.unwrap_or(Phase::Approach) {
+            Phase::Approach => {
+                let (dx, dy) = (-distance * sin - x, -distance * cos - y);
+                if dx * dx + dy * dy < APPROACH_RADIUS * APPROACH_RADIUS {
+                    Phase::Glide
+                } else {
+                    self.phase = Some(Phase::Approach);
+                    let altitude = distance * slope;
+                    return Target { heading: azimuth(dx, dy), altitude, pitch: None, throttle };
+                }
+            }
+            Phase::Glide if height <= self.config.flare_height as f32 => Phase::Flare,
+            Phase::Flare if ins.velocity_vector.scalar().raw < LANDED_SPEED => Phase::Landed,
+            phase => phase,
+        };
+        self.phase = Some(phase);
+
+        let correction = (cross * CROSS_TRACK_GAIN).clamp(-MAX_CORRECTION, MAX_CORRECTION);
+        let track = (heading - correction + 360.0) % 360.0;
+        match phase {
+            Phase::Flare => {
+                let pitch = Some(self.config.flare_pitch as f32);
+                Target { heading, altitude: 0.0, pitch, throttle: 0 }
+            }
+            Phase::Landed => Target { heading, altitude: 0.0, pitch: Some(0.0), throttle: 0 },
+            _ => {
+                let altitude = (-along).max(0.0) * slope;
+                Target { heading: track, altitude, pitch: None, throttle }
+            }
+        }
+    }
+}
+
+mod test {
+    #[test]
+    fn test_autoland() {
+        use super::{Autoland, Phase, Target};
+        use crate::{
+            config::fcs::Autoland as Config,
+            ins::out::INS,
+            types::measurement::{unit::Ms, VelocityVector, ENU},
+        };
+
+        let config = Config { heading: 90, ..Default::default() };
+        let mut autoland = Autoland::new(&config);
+        let mut ins = INS::default();
+        ins.displacement.raw.z = 100.0;
+        ins.velocity_vector = VelocityVector::new(20.0, 0.0, 0.0, Ms, ENU);
+
+        // Should head to approach point west of home
+        let target = autoland.update(&ins);
+        assert_eq!(autoland.phase(), Some(Phase::Approach));
+        assert!((target.heading - 270.0).abs() < 0.1);
+        assert!((target.altitude - 26.25).abs() < 0.1);
+        assert_eq!(target.throttle, u16::MAX / 5 * 2);
+
+        // Should descend on glide slope, correcting towards runway center line
+        ins.displacement.raw.x = -280.0;
+        ins.displacement.raw.y = 10.0;
+        ins.displacement.raw.z = 25.0;
+        let target = autoland.update(&ins);
+        assert_eq!(autoland.phase(), Some(Phase::Glide));
+        assert!((target.heading - 100.0).abs() < 0.1);
+        assert!((target.altitude - 24.5).abs() < 0.1);
+
+        // Should flare and cut throttle
+        ins.displacement.raw.x = -30.0;
+        ins.displacement.raw.y = 0.0;
+        ins.displacement.raw.z = 2.0;
+        let target = autoland.update(&ins);
+        assert_eq!(autoland.phase(), Some(Phase::Flare));
+        assert_eq!(target, Target { heading: 90.0, altitude: 0.0, pitch: Some(5.0), throttle: 0 });
+
+        ins.velocity_vector = VelocityVector::new(1.0, 0.0, 0.0, Ms, ENU);
+        autoland.update(&ins);
+        assert_eq!(autoland.phase(), Some(Phase::Landed));
+
+        autoland.disengage();
+        assert_eq!(autoland.phase(), None);
+    }
+}
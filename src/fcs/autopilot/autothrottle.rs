@@ -0,0 +1,95 @@
+use pid::Pid;
+
+use crate::config::fcs::AutoThrottle as Config;
+
+pub struct AutoThrottle {
+    interval: f32,
+    config: Config,
+    pid: Pid<f32>,
+    engaged: bool,
+    trim: f32,
+    throttle: f32,
+}
+
+fn config_to_pid(config: &Config, interval: f32) -> Pid<f32> {
+    let (kp, ki, kd): (f32, f32, f32) = (config.kp.into(), config.ki.into(), config.kd.into());
+    Pid::new(kp, ki * interval, kd / interval, 100.0, 100.0, 100.0, 100.0, 0.0)
+}
+
+impl AutoThrottle {
+    pub fn new(config: &Config, sample_rate: usize) -> Self {
+        let interval = 1.0 / sample_rate as f32;
+        Self {
+            interval,
+            config: *config,
+            pid: config_to_pid(config, interval),
+            engaged: false,
+            trim: 0.0,
+            throttle: 0.0,
+        }
+    }
+
+    pub fn reconfigure(&mut self, config: &Config) {
+        let setpoint = self.pid.setpoint;
+        self.config = *config;
+        self.pid = config_to_pid(config, self.interval);
+        self.pid.setpoint = setpoint;
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.engaged
+    }
+
+    pub fn disengage(&mut self) {
+        self.engaged = false;
+    }
+
+    /// Speed in m/s, current speed and throttle will be held once engaged
+    pub fn update(&mut self, speed: f32, throttle: u16) -> u16 {
+        if !self.engaged {
+            self.pid = config_to_pid(&self.config, self.interval);
+            self.pid.setpoint = speed;
+            self.trim = throttle as f32 / u16::MAX as f32;
+            self.throttle = self.trim;
+            self.engaged = true;
+        }
+        let min = self.config.min_throttle as f32 / 100.0;
+        let max = f32::max(self.config.max_throttle as f32 / 100.0, min);
+        let output = self.pid.next_control_output(speed).output / 100.0;
+        let target = (self.trim + output).clamp(min, max);
+        let slew = self.config.slew_rate as f32 / 100.0 * self.interval;
+        self.throttle += (target - self.throttle).clamp(-slew, slew);
+        (self.throttle * u16::MAX as f32) as u16
+    }
+}
+
+mod test {
+    #[test]
+    fn test_auto_throttle() {
+        use super::AutoThrottle;
+        use crate::config::fcs::AutoThrottle as Config;
+
+        let mut auto_throttle = AutoThrottle::new(&Config::default(), 10);
+        let half = u16::MAX / 2;
+        let step = u16::MAX / 50; // 20% per second at 10Hz
+
+        // Should hold current throttle when engaged
+        let throttle = auto_throttle.update(20.0, half);
+        assert!(throttle.abs_diff(half) <= 1);
+        assert!(auto_throttle.is_engaged());
+
+        // Should increase throttle within slew rate when slowing down
+        let throttle = auto_throttle.update(15.0, 0);
+        assert!(throttle > half && throttle <= half + step + 1);
+
+        // Should reduce throttle when too fast
+        let prev = auto_throttle.update(15.0, 0);
+        let throttle = auto_throttle.update(30.0, 0);
+        assert!(throttle < prev);
+
+        // Should re-capture speed after re-engaged
+        auto_throttle.disengage();
+        let throttle = auto_throttle.update(30.0, u16::MAX);
+        assert!(throttle.abs_diff(u16::MAX) <= 1);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::config::fcs::HeadingHold as Config;
+
+pub struct HeadingHold {
+    config: Config,
+    target: Option<f32>,
+}
+
+/// Shortest turn from current heading to target heading in degrees, positive for right turn
+pub fn heading_error(target: f32, current: f32) -> f32 {
+    let error = (target - current) % 360.0;
+    match () {
+        _ if error > 180.0 => error - 360.0,
+        _ if error <= -180.0 => error + 360.0,
+        _ => error,
+    }
+}
+
+impl HeadingHold {
+    pub fn new(config: &Config) -> Self {
+        Self { config: *config, target: None }
+    }
+
+    pub fn reconfigure(&mut self, config: &Config) {
+        self.config = *config;
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.target.is_some()
+    }
+
+    pub fn disengage(&mut self) {
+        self.target = None;
+    }
+
+    /// Heading in degrees, overrides captured heading
+    pub fn set_target(&mut self, heading: f32) {
+        self.target = Some(heading);
+    }
+
+    /// Heading in degrees, returns bank angle in degrees,
+    /// current heading will be held once engaged
+    pub fn update(&mut self, heading: f32) -> f32 {
+        let target = *self.target.get_or_insert(heading);
+        let kp: f32 = self.config.kp.into();
+        let max_roll = self.config.max_roll as f32;
+        (heading_error(target, heading) * kp).clamp(-max_roll, max_roll)
+    }
+}
+
+mod test {
+    #[test]
+    fn test_heading_error() {
+        use super::heading_error;
+
+        assert_eq!(heading_error(90.0, 80.0), 10.0);
+        assert_eq!(heading_error(350.0, 10.0), -20.0);
+        assert_eq!(heading_error(10.0, 350.0), 20.0);
+        assert_eq!(heading_error(0.0, 180.0), 180.0);
+    }
+
+    #[test]
+    fn test_heading_hold() {
+        use super::HeadingHold;
+        use crate::config::fcs::HeadingHold as Config;
+
+        let mut heading_hold = HeadingHold::new(&Config::default());
+        assert_eq!(heading_hold.update(350.0), 0.0);
+        assert!(heading_hold.is_engaged());
+
+        assert_eq!(heading_hold.update(340.0), 10.0);
+        assert_eq!(heading_hold.update(5.0), -15.0);
+        assert_eq!(heading_hold.update(180.0), 30.0);
+
+        heading_hold.set_target(90.0);
+        assert_eq!(heading_hold.update(100.0), -10.0);
+    }
+}
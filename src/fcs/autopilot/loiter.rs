@@ -0,0 +1,132 @@
+#[cfg(not(any(test, feature = "std")))]
+use micromath::F32Ext;
+use nalgebra::Vector2;
+
+use super::heading::heading_error;
+use crate::{
+    config::{
+        fcs::{Direction, Loiter as Config},
+        Mission,
+    },
+    ins::out::INS,
+    types::{coordinate::Position, measurement::GRAVITY},
+};
+
+const HEADING_GAIN: f32 = 1.0; // degree of bank per degree of heading error
+const RADIAL_GAIN: f32 = 1.0; // degree of heading correction per meter off circle
+
+/// Center in meters relative to home and radius in meters
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Orbit {
+    pub center: Vector2<f32>,
+    pub radius: f32,
+}
+
+pub struct Loiter {
+    config: Config,
+    max_roll: f32,
+    waypoint: Option<Position>,
+    orbit: Option<Orbit>,
+}
+
+fn azimuth(vector: Vector2<f32>) -> f32 {
+    let azimuth = vector.x.atan2(vector.y).to_degrees();
+    if azimuth < 0.0 { azimuth + 360.0 } else { azimuth }
+}
+
+impl Loiter {
+    pub fn new(config: &Config, mission: &Mission, max_roll: u8) -> Self {
+        let mut loiter = Self { config: *config, max_roll: 0.0, waypoint: None, orbit: None };
+        loiter.reconfigure(config, mission, max_roll);
+        loiter
+    }
+
+    pub fn reconfigure(&mut self, config: &Config, mission: &Mission, max_roll: u8) {
+        self.config = *config;
+        self.max_roll = max_roll as f32;
+        let index = (config.waypoint as usize).checked_sub(1);
+        self.waypoint = index.and_then(|i| mission.waypoints.get(i)).map(|w| w.position);
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.orbit.is_some()
+    }
+
+    pub fn disengage(&mut self) {
+        self.orbit = None;
+    }
+
+    /// Overrides orbit captured from configured waypoint or current position
+    pub fn set_target(&mut self, orbit: Orbit) {
+        self.orbit = Some(orbit);
+    }
+
+    /// Heading in degrees, returns bank angle in degrees towards tangent of orbit,
+    /// limited by envelop maximum roll
+    pub fn update(&mut self, ins: &INS, heading: f32) -> f32 {
+        let position = Vector2::new(ins.displacement.x().raw, ins.displacement.y().raw);
+        let waypoint = self.waypoint;
+        let radius = self.config.radius as f32;
+        let orbit = *self.orbit.get_or_insert_with(|| {
+            let center = match waypoint {
+                Some(waypoint) => {
+                    let delta = (waypoint - ins.position).t(|v| v as f32);
+                    position + Vector2::new(delta.x().raw, delta.y().raw)
+                }
+                None => position,
+            };
+            Orbit { center, radius }
+        });
+
+        let offset = position - orbit.center;
+        let correction = ((offset.norm() - orbit.radius) * RADIAL_GAIN).clamp(-90.0, 90.0);
+        let speed = ins.velocity_vector.scalar().raw;
+        let bank = (speed * speed / (GRAVITY * orbit.radius)).atan().to_degrees();
+        let bank = bank * (1.0 - correction.abs() / 90.0);
+        let (target, bank) = match self.config.direction {
+            Direction::Clockwise => (azimuth(offset) + 90.0 + correction, bank),
+            Direction::CounterClockwise => (azimuth(offset) - 90.0 - correction, -bank),
+        };
+        let roll = bank + heading_error(target, heading) * HEADING_GAIN;
+        roll.clamp(-self.max_roll, self.max_roll)
+    }
+}
+
+mod test {
+    #[test]
+    fn test_loiter() {
+        use super::Loiter;
+        use crate::{
+            config::{
+                fcs::{Direction, Loiter as Config},
+                Mission,
+            },
+            ins::out::INS,
+            types::measurement::{unit::Ms, VelocityVector, ENU},
+        };
+
+        let mut loiter = Loiter::new(&Config::default(), &Mission::default(), 67);
+        let velocity_vector = VelocityVector::new(20.0, 0.0, 0.0, Ms, ENU);
+        let mut ins = INS { velocity_vector, ..Default::default() };
+        assert!(!loiter.is_engaged());
+        // Should head out of center towards circle
+        assert_eq!(loiter.update(&ins, 90.0), -67.0);
+        assert!(loiter.is_engaged());
+
+        // Should hold bank angle on circle flying tangent
+        ins.displacement.raw.y = 100.0;
+        let roll = loiter.update(&ins, 90.0);
+        assert!((roll - 22.19).abs() < 0.01);
+
+        // Should bank left flying counter-clockwise
+        let config = Config { direction: Direction::CounterClockwise, ..Default::default() };
+        let mut loiter = Loiter::new(&config, &Mission::default(), 30);
+        ins.displacement.raw.y = 0.0;
+        loiter.update(&ins, 270.0);
+        ins.displacement.raw.y = 100.0;
+        let roll = loiter.update(&ins, 270.0);
+        assert!((roll + 22.19).abs() < 0.01);
+        ins.displacement.raw.y = 150.0;
+        assert_eq!(loiter.update(&ins, 270.0), -30.0);
+    }
+}
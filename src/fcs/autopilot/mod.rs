@@ -0,0 +1,264 @@
+pub mod altitude;
+pub mod autoland;
+pub mod autothrottle;
+pub mod heading;
+pub mod loiter;
+pub mod navigation;
+
+use nalgebra::{Vector2, Vector3};
+
+use crate::{
+    config::{
+        inputs::command::{Autopilot as Mode, Id},
+        Config, Failsafe,
+    },
+    fcs::failsafe::Stage,
+    imu::out::IMU,
+    ins::out::INS,
+    types::control::Control,
+};
+
+/// Stick deflection beyond which pilot overrides autopilot
+pub const OVERRIDE_THRESHOLD: u16 = i16::MAX as u16 / 20;
+
+/// Converts attitude error into rate setpoint in the same way as flight envelop
+pub fn attitude_to_rate(target: f32, current: f32, max_rate: f32) -> f32 {
+    (target - current).clamp(-max_rate, max_rate)
+}
+
+pub struct Autopilot {
+    max_rates: Vector3<f32>,
+    failsafe: Failsafe,
+    pub auto_throttle: autothrottle::AutoThrottle,
+    pub altitude_hold: altitude::AltitudeHold,
+    pub heading_hold: heading::HeadingHold,
+    pub autoland: autoland::Autoland,
+    pub loiter: loiter::Loiter,
+    pub navigation: navigation::Navigation,
+}
+
+fn max_rates(config: &Config) -> Vector3<f32> {
+    let pids = &config.fcs.pids;
+    Vector3::new(pids.roll.max_rate as f32, pids.pitch.max_rate as f32, pids.yaw.max_rate as f32)
+}
+
+impl Autopilot {
+    pub fn new(config: &Config, sample_rate: usize) -> Self {
+        let autopilot = &config.fcs.autopilot;
+        let max_roll = config.fcs.envelop.max_roll;
+        Self {
+            max_rates: max_rates(config),
+            failsafe: config.failsafe,
+            auto_throttle: autothrottle::AutoThrottle::new(&autopilot.auto_throttle, sample_rate),
+            altitude_hold: altitude::AltitudeHold::new(&autopilot.altitude_hold),
+            heading_hold: heading::HeadingHold::new(&autopilot.heading_hold),
+            autoland: autoland::Autoland::new(&autopilot.autoland),
+            loiter: loiter::Loiter::new(&autopilot.loiter, &config.mission, max_roll),
+            navigation: navigation::Navigation::new(&config.mission),
+        }
+    }
+
+    pub fn reconfigure(&mut self, config: &Config) {
+        let autopilot = &config.fcs.autopilot;
+        self.max_rates = max_rates(config);
+        self.failsafe = config.failsafe;
+        self.auto_throttle.reconfigure(&autopilot.auto_throttle);
+        self.altitude_hold.reconfigure(&autopilot.altitude_hold);
+        self.heading_hold.reconfigure(&autopilot.heading_hold);
+        self.autoland.reconfigure(&autopilot.autoland);
+        let max_roll = config.fcs.envelop.max_roll;
+        self.loiter.reconfigure(&autopilot.loiter, &config.mission, max_roll);
+        self.navigation.reconfigure(&config.mission);
+    }
+
+    /// Overrides pilot rate setpoints and throttle with engaged autopilot modes
+    pub fn update(
+        &mut self,
+        control: &Control,
+        rates: Vector3<f32>,
+        imu: &IMU,
+        ins: &INS,
+    ) -> (Vector3<f32>, u16) {
+        let (input, attitude) = (control.axes, imu.attitude);
+        let mut rates = rates;
+
+        let autoland = control.contains(Id::Autopilot(Mode::Autoland));
+        let landing = if autoland {
+            Some(self.autoland.update(ins))
+        } else {
+            self.autoland.disengage();
+            None
+        };
+        let loiter = !autoland && control.contains(Id::Autopilot(Mode::Loiter));
+        if !loiter {
+            self.loiter.disengage();
+        }
+        let navigation = control.contains(Id::Autopilot(Mode::Navigation));
+        if let Some(landing) = landing {
+            self.heading_hold.set_target(landing.heading);
+            self.altitude_hold.set_target(landing.altitude);
+        } else if navigation && !loiter {
+            let (heading, altitude) = self.navigation.update(ins);
+            self.heading_hold.set_target(heading);
+            if let Some(altitude) = altitude {
+                self.altitude_hold.set_target(altitude);
+            }
+        }
+
+        let heading_hold =
+            autoland || loiter || navigation || control.contains(Id::Autopilot(Mode::HeadingHold));
+        if heading_hold && input.roll.unsigned_abs() < OVERRIDE_THRESHOLD {
+            let roll = if loiter {
+                self.loiter.update(ins, attitude.yaw)
+            } else {
+                self.heading_hold.update(attitude.yaw)
+            };
+            rates.x = attitude_to_rate(roll, attitude.roll, self.max_rates.x);
+        } else {
+            self.heading_hold.disengage();
+        }
+
+        let altitude_hold =
+            autoland || loiter || navigation || control.contains(Id::Autopilot(Mode::AltitudeHold));
+        if altitude_hold && input.pitch.unsigned_abs() < OVERRIDE_THRESHOLD {
+            let pitch = match landing.and_then(|landing| landing.pitch) {
+                Some(pitch) => pitch,
+                None => self.altitude_hold.update(ins.displacement.z().raw),
+            };
+            rates.y = attitude_to_rate(pitch, attitude.pitch, self.max_rates.y);
+        } else {
+            self.altitude_hold.disengage();
+        }
+
+        let throttle = if let Some(landing) = landing {
+            self.auto_throttle.disengage();
+            landing.throttle
+        } else if control.contains(Id::Autopilot(Mode::AutoThrottle)) {
+            self.auto_throttle.update(ins.velocity_vector.scalar().raw, input.throttle)
+        } else {
+            self.auto_throttle.disengage();
+            input.throttle
+        };
+        (rates, throttle)
+    }
+
+    /// Rate setpoints and throttle flying specified failsafe stage,
+    /// hold stage keeps specified throttle which was last commanded
+    pub fn failsafe(
+        &mut self,
+        stage: Stage,
+        imu: &IMU,
+        ins: &INS,
+        hold_throttle: u16,
+    ) -> (Vector3<f32>, u16) {
+        let attitude = imu.attitude;
+        let height = ins.displacement.z().raw;
+        let safe_altitude = self.failsafe.safe_altitude as f32;
+        self.autoland.disengage();
+        let (roll, pitch) = match stage {
+            Stage::Hold => {
+                self.heading_hold.disengage();
+                self.altitude_hold.disengage();
+                (0.0, 0.0)
+            }
+            Stage::Climb => {
+                self.heading_hold.disengage();
+                self.altitude_hold.set_target(safe_altitude);
+                (0.0, self.altitude_hold.update(height))
+            }
+            Stage::Return => {
+                let home = (ins.displacement * -1.0).azimuth();
+                self.heading_hold.set_target(home as f32);
+                self.altitude_hold.set_target(safe_altitude);
+                (self.heading_hold.update(attitude.yaw), self.altitude_hold.update(height))
+            }
+            Stage::Loiter => {
+                let radius = self.failsafe.loiter_radius as f32;
+                self.loiter.set_target(loiter::Orbit { center: Vector2::zeros(), radius });
+                self.altitude_hold.set_target(safe_altitude);
+                (self.loiter.update(ins, attitude.yaw), self.altitude_hold.update(height))
+            }
+        };
+        self.auto_throttle.disengage();
+        let rates = Vector3::new(
+            attitude_to_rate(roll, attitude.roll, self.max_rates.x),
+            attitude_to_rate(pitch, attitude.pitch, self.max_rates.y),
+            0.0,
+        );
+        let throttle = match stage {
+            Stage::Hold => hold_throttle,
+            _ => (self.failsafe.throttle as u32 * u16::MAX as u32 / 100) as u16,
+        };
+        (rates, throttle)
+    }
+}
+
+mod test {
+    #[test]
+    fn test_autopilot_override() {
+        use nalgebra::Vector3;
+
+        use super::Autopilot;
+        use crate::{
+            config::{
+                inputs::command::{Autopilot as Mode, Id},
+                Config,
+            },
+            imu::out::IMU,
+            ins::out::INS,
+            types::control::Control,
+        };
+
+        let mut autopilot = Autopilot::new(&Config::default(), 50);
+        let mut control = Control::default();
+        control.commands.push(Id::Autopilot(Mode::HeadingHold)).ok();
+        control.commands.push(Id::Autopilot(Mode::AltitudeHold)).ok();
+        let (mut imu, mut ins) = (IMU::default(), INS::default());
+        imu.attitude.yaw = 90.0;
+        ins.displacement.raw.z = 100.0;
+
+        let (rates, _) = autopilot.update(&control, Vector3::new(1.0, 1.0, 1.0), &imu, &ins);
+        assert_eq!(rates, Vector3::new(0.0, 0.0, 1.0));
+
+        // Should turn right and climb to captured heading and altitude
+        imu.attitude.yaw = 80.0;
+        ins.displacement.raw.z = 95.0;
+        let (rates, _) = autopilot.update(&control, Vector3::zeros(), &imu, &ins);
+        assert!(rates.x > 0.0 && rates.y > 0.0);
+
+        // Should yield to pilot and recapture afterwards
+        control.axes.roll = i16::MIN;
+        let (rates, _) = autopilot.update(&control, Vector3::new(-30.0, 0.0, 0.0), &imu, &ins);
+        assert_eq!(rates.x, -30.0);
+        control.axes.roll = 0;
+        let (rates, _) = autopilot.update(&control, Vector3::zeros(), &imu, &ins);
+        assert_eq!(rates.x, 0.0);
+    }
+
+    #[test]
+    fn test_autopilot_failsafe() {
+        use super::Autopilot;
+        use crate::{config::Config, fcs::failsafe::Stage, imu::out::IMU, ins::out::INS};
+
+        let mut autopilot = Autopilot::new(&Config::default(), 50);
+        let (imu, mut ins) = (IMU::default(), INS::default());
+        ins.displacement.raw.x = 100.0;
+
+        // Should keep last commanded throttle while holding
+        let (_, throttle) = autopilot.failsafe(Stage::Hold, &imu, &ins, u16::MAX / 4);
+        assert_eq!(throttle, u16::MAX / 4);
+
+        let (rates, throttle) = autopilot.failsafe(Stage::Climb, &imu, &ins, u16::MAX / 4);
+        assert!(rates.x == 0.0 && rates.y > 0.0);
+        assert_eq!(throttle, u16::MAX / 2);
+
+        // Should turn left toward home from east of home
+        ins.displacement.raw.z = 50.0;
+        let (rates, _) = autopilot.failsafe(Stage::Return, &imu, &ins, 0);
+        assert!(rates.x < 0.0 && rates.y == 0.0);
+
+        // Should turn right orbiting home clockwise from east of home
+        let (rates, _) = autopilot.failsafe(Stage::Loiter, &imu, &ins, 0);
+        assert!(rates.x > 0.0 && rates.y == 0.0);
+    }
+}
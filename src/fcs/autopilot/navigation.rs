@@ -0,0 +1,113 @@
+use crate::{
+    config::Mission,
+    ins::out::INS,
+    types::{
+        coordinate::Position,
+        measurement::unit::Meter,
+        waypoint::{Steerpoint, Waypoint},
+    },
+};
+
+pub struct Navigation {
+    mission: Mission,
+    index: usize,
+}
+
+fn home(ins: &INS) -> Position {
+    ins.position - ins.displacement.t(|v| v as i32)
+}
+
+impl Navigation {
+    pub fn new(mission: &Mission) -> Self {
+        Self { mission: mission.clone(), index: 0 }
+    }
+
+    pub fn reconfigure(&mut self, mission: &Mission) {
+        self.mission = mission.clone();
+        self.index = core::cmp::min(self.index, self.mission.waypoints.len());
+    }
+
+    /// Active steerpoint, home after the last waypoint reached
+    pub fn steerpoint(&self, ins: &INS) -> Steerpoint {
+        match self.mission.waypoints.get(self.index) {
+            Some(waypoint) => {
+                Steerpoint { index: self.index as u8 + 1, waypoint: waypoint.clone() }
+            }
+            None => Steerpoint {
+                index: 0,
+                waypoint: Waypoint { position: home(ins), ..Default::default() },
+            },
+        }
+    }
+
+    /// Returns heading in degrees and altitude in meters relative to home,
+    /// altitude not specified for home
+    pub fn update(&mut self, ins: &INS) -> (f32, Option<f32>) {
+        let radius = self.mission.acceptance_radius as i64;
+        while let Some(waypoint) = self.mission.waypoints.get(self.index) {
+            let delta = waypoint.position - ins.position;
+            let (x, y) = (delta.x().raw as i64, delta.y().raw as i64);
+            if x * x + y * y > radius * radius {
+                break;
+            }
+            self.index += 1;
+        }
+
+        let steerpoint = self.steerpoint(ins);
+        let heading = (steerpoint.waypoint.position - ins.position).t(|v| v as f32).azimuth();
+        let altitude = match steerpoint.index {
+            0 => None,
+            _ => {
+                let altitude = steerpoint.waypoint.position.altitude - home(ins).altitude;
+                Some(altitude.t(|v| v as f32).u(Meter).raw)
+            }
+        };
+        (heading as f32, altitude)
+    }
+}
+
+mod test {
+    #[test]
+    fn test_navigation() {
+        use heapless::String;
+
+        use super::Navigation;
+        use crate::{
+            config::Mission,
+            ins::out::INS,
+            types::{
+                measurement::{unit::Meter, Displacement, ENU},
+                waypoint::Waypoint,
+            },
+        };
+
+        let mut mission = Mission::default();
+        for (i, (x, y)) in [(0, 1000), (1000, 1000)].iter().enumerate() {
+            let displacement = Displacement::new(*x, *y, 100 * (i as i32 + 1), Meter, ENU);
+            let position = INS::default().position + displacement;
+            mission.waypoints.push(Waypoint { name: String::from("WP"), position }).ok();
+        }
+        let mut navigation = Navigation::new(&mission);
+        let mut ins = INS::default();
+
+        let (heading, altitude) = navigation.update(&ins);
+        assert_eq!((heading, altitude), (0.0, Some(100.0)));
+        assert_eq!(navigation.steerpoint(&ins).index, 1);
+
+        // Should sequence to next waypoint within acceptance radius
+        let displacement = Displacement::new(0.0, 960.0, 100.0, Meter, ENU);
+        ins.position = ins.position + displacement.t(|v| v as i32);
+        ins.displacement = displacement;
+        let (heading, altitude) = navigation.update(&ins);
+        assert_eq!(navigation.steerpoint(&ins).index, 2);
+        assert_eq!((heading, altitude), (87.0, Some(200.0)));
+
+        // Should return home after last waypoint
+        let displacement = Displacement::new(1000.0, 1000.0, 100.0, Meter, ENU);
+        ins.position = ins.position + Displacement::new(1000, 40, 0, Meter, ENU);
+        ins.displacement = displacement;
+        let (heading, altitude) = navigation.update(&ins);
+        assert_eq!(navigation.steerpoint(&ins).index, 0);
+        assert_eq!((heading, altitude), (225.0, None));
+    }
+}
@@ -0,0 +1,227 @@
+use core::f32::consts::PI;
+
+use fixed_point::FixedPoint;
+use fugit::NanosDurationU64 as Duration;
+use nalgebra::Vector3;
+
+use super::autopilot::OVERRIDE_THRESHOLD;
+use crate::{config::fcs::PIDs, types::control::Axes};
+
+const RELAY: f32 = 0.2; // control-surface ratio of relay output
+const HYSTERESIS: f32 = 5.0; // degree per second
+const SETTLING_CYCLES: usize = 2;
+const MEASURED_CYCLES: usize = 6;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Gains {
+    pub kp: f32,
+    pub ki: f32,
+    pub kd: f32,
+    pub max_rate: f32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Status {
+    Idle,
+    Tuning(usize, f32), // axis index and control-surface output
+    Done([Gains; 3]),
+}
+
+#[derive(Default)]
+struct Relay {
+    positive: bool,
+    cycle_start: Option<Duration>,
+    cycles: usize,
+    peak: f32,
+    periods: f32,
+    peaks: f32,
+}
+
+impl Relay {
+    /// Relay output opposing gyro rate, switching beyond hysteresis
+    fn update(&mut self, now: Duration, rate: f32) -> f32 {
+        self.peak = self.peak.max(rate.abs());
+        let switch = if self.positive { rate > HYSTERESIS } else { rate < -HYSTERESIS };
+        if switch {
+            self.positive = !self.positive;
+            if self.positive {
+                if let Some(start) = self.cycle_start {
+                    self.cycles += 1;
+                    if self.cycles > SETTLING_CYCLES {
+                        self.periods += (now - start).to_micros() as f32 / 1_000_000.0;
+                        self.peaks += self.peak;
+                    }
+                }
+                self.cycle_start = Some(now);
+                self.peak = 0.0;
+            }
+        }
+        if self.positive { RELAY } else { -RELAY }
+    }
+
+    /// Ziegler-Nichols some-overshoot rule from ultimate gain and period,
+    /// ki per second and kd in seconds
+    fn gains(&self) -> Option<Gains> {
+        if self.cycles < SETTLING_CYCLES + MEASURED_CYCLES {
+            return None;
+        }
+        let period = self.periods / MEASURED_CYCLES as f32;
+        let amplitude = self.peaks / MEASURED_CYCLES as f32;
+        let ku = 4.0 * RELAY * 100.0 / (PI * amplitude);
+        Some(Gains {
+            kp: 0.33 * ku,
+            ki: 0.66 * ku / period,
+            kd: 0.11 * ku * period,
+            max_rate: amplitude / RELAY,
+        })
+    }
+}
+
+/// Tunes roll, pitch and yaw in sequence with relay oscillation
+#[derive(Default)]
+pub struct Autotune {
+    axis: usize,
+    relay: Relay,
+    gains: [Gains; 3],
+    finished: bool,
+}
+
+fn fixed(value: f32) -> FixedPoint<u16, 2> {
+    FixedPoint((value * 100.0 + 0.5).clamp(0.0, u16::MAX as f32) as u16)
+}
+
+/// Writes tuned gains into PID configuration, whose ki and kd apply
+/// once each FCS update at sample rate
+pub fn apply(config: &mut PIDs, gains: &[Gains; 3], sample_rate: f32) {
+    let pids = [&mut config.roll, &mut config.pitch, &mut config.yaw];
+    for (pid, gains) in pids.into_iter().zip(gains.iter()) {
+        pid.kp = fixed(gains.kp);
+        pid.ki = fixed(gains.ki / sample_rate);
+        pid.kd = fixed(gains.kd * sample_rate);
+        pid.max_rate = gains.max_rate as u16;
+    }
+}
+
+impl Autotune {
+    pub fn disengage(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Takes over current axis, restarts measuring on pilot override
+    pub fn update(&mut self, now: Duration, input: &Axes, rates: Vector3<f32>) -> Status {
+        if self.finished {
+            return Status::Idle;
+        }
+        let stick = [input.roll, input.pitch, input.yaw][self.axis];
+        if stick.unsigned_abs() >= OVERRIDE_THRESHOLD {
+            self.relay = Relay::default();
+            return Status::Idle;
+        }
+        let output = self.relay.update(now, rates[self.axis]);
+        let gains = match self.relay.gains() {
+            Some(gains) => gains,
+            None => return Status::Tuning(self.axis, output),
+        };
+        self.gains[self.axis] = gains;
+        self.relay = Relay::default();
+        self.axis += 1;
+        if self.axis < self.gains.len() {
+            return Status::Tuning(self.axis - 1, 0.0);
+        }
+        self.finished = true;
+        Status::Done(self.gains)
+    }
+}
+
+mod test {
+    #[test]
+    fn test_autotune() {
+        use fugit::NanosDurationU64 as Duration;
+        use nalgebra::Vector3;
+
+        use super::{Autotune, Status};
+        use crate::types::control::Axes;
+
+        // First order rate response with transport delay
+        let (gain, tau, delay, rate) = (300.0, 0.1, 3, 100);
+        let mut autotune = Autotune::default();
+        let mut rates = Vector3::<f32>::zeros();
+        let mut outputs = [[0.0f32; 3]; 4];
+        let mut tuned = None;
+        for i in 0..rate * 60 {
+            let now = Duration::millis(i as u64 * 1000 / rate as u64);
+            let mut output = [0.0f32; 3];
+            match autotune.update(now, &Axes::default(), rates) {
+                Status::Tuning(axis, value) => output[axis] = value,
+                Status::Done(gains) => tuned = Some(gains),
+                Status::Idle => break,
+            }
+            outputs.rotate_right(1);
+            outputs[0] = output;
+            for axis in 0..3 {
+                let delayed = outputs[delay][axis];
+                rates[axis] += (gain * delayed - rates[axis]) / tau / rate as f32;
+            }
+        }
+        let tuned = tuned.unwrap();
+        for gains in tuned.iter() {
+            assert!(gains.kp > 0.1 && gains.kp < 1.0);
+            assert!(gains.ki > 0.0 && gains.kd > 0.0);
+            assert!(gains.max_rate > 50.0 && gains.max_rate < 300.0);
+        }
+        assert_eq!(autotune.update(Duration::secs(61), &Axes::default(), rates), Status::Idle);
+    }
+
+    #[test]
+    fn test_autotune_override() {
+        use fugit::NanosDurationU64 as Duration;
+        use nalgebra::Vector3;
+
+        use super::{Autotune, Status};
+        use crate::types::control::Axes;
+
+        let mut autotune = Autotune::default();
+        let rates = Vector3::new(10.0, 0.0, 0.0);
+        let status = autotune.update(Duration::secs(1), &Axes::default(), rates);
+        assert_eq!(status, Status::Tuning(0, -0.2));
+        let input = Axes { roll: i16::MAX / 2, ..Default::default() };
+        assert_eq!(autotune.update(Duration::secs(1), &input, rates), Status::Idle);
+    }
+
+    #[test]
+    fn test_apply() {
+        use super::{apply, Gains};
+        use crate::config::fcs::PIDs;
+
+        let mut config = PIDs::default();
+        let gains = Gains { kp: 0.5, ki: 0.5, kd: 0.024, max_rate: 180.0 };
+        apply(&mut config, &[gains; 3], 50.0);
+        assert_eq!(config.pitch.kp.0, 50);
+        assert_eq!(config.pitch.ki.0, 1);
+        assert_eq!(config.pitch.kd.0, 120);
+        assert_eq!(config.yaw.max_rate, 180);
+    }
+
+    #[test]
+    fn test_apply_measured() {
+        use super::{apply, Relay, MEASURED_CYCLES, SETTLING_CYCLES};
+        use crate::config::fcs::PIDs;
+
+        // 120 degree per second amplitude at 4hz oscillation
+        let relay = Relay {
+            cycles: SETTLING_CYCLES + MEASURED_CYCLES,
+            periods: 0.25 * MEASURED_CYCLES as f32,
+            peaks: 120.0 * MEASURED_CYCLES as f32,
+            ..Default::default()
+        };
+        let gains = relay.gains().unwrap();
+        let mut config = PIDs::default();
+        apply(&mut config, &[gains; 3], 50.0);
+        let (kp, ki, kd): (f32, f32, f32) =
+            (config.roll.kp.into(), config.roll.ki.into(), config.roll.kd.into());
+        assert!((kp - 0.07).abs() < 0.01);
+        assert!((ki - 0.01).abs() < 0.005);
+        assert!((kd - 0.29).abs() < 0.01);
+        assert_eq!(config.roll.max_rate, 600);
+    }
+}
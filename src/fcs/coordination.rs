@@ -0,0 +1,65 @@
+#[cfg(not(any(test, feature = "std")))]
+use micromath::F32Ext;
+
+use crate::{
+    config::fcs::{Configuration, TurnCoordination as Config},
+    types::measurement::GRAVITY,
+};
+
+const MAX_BANK_ANGLE: f32 = 80.0; // tan grows unbounded towards 90°
+
+pub struct TurnCoordination {
+    enable: bool,
+    min_speed: f32,
+    gain: f32,
+}
+
+impl TurnCoordination {
+    pub fn new(config: &Config, configuration: Configuration) -> Self {
+        let rudder = matches!(configuration, Configuration::Airplane | Configuration::VTail);
+        Self {
+            enable: config.enable && rudder,
+            min_speed: config.min_speed as f32,
+            gain: config.gain as f32 / 100.0,
+        }
+    }
+
+    pub fn reconfigure(&mut self, config: &Config, configuration: Configuration) {
+        *self = Self::new(config, configuration);
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enable
+    }
+
+    /// Yaw rate in degree per second for coordinated turn at bank angle, g·tan(φ)/V
+    pub fn yaw_rate(&self, roll: f32, speed: Option<f32>) -> f32 {
+        let speed = match speed {
+            Some(speed) if self.enable => speed.max(self.min_speed),
+            _ => return 0.0,
+        };
+        let roll = roll.clamp(-MAX_BANK_ANGLE, MAX_BANK_ANGLE).to_radians();
+        (GRAVITY * roll.tan() / speed).to_degrees() * self.gain
+    }
+}
+
+mod test {
+    #[test]
+    fn test_turn_coordination() {
+        use super::TurnCoordination;
+        use crate::config::fcs::{Configuration, TurnCoordination as Config};
+
+        let config = Config { enable: true, ..Default::default() };
+        let coordination = TurnCoordination::new(&config, Configuration::Airplane);
+        assert_eq!(coordination.yaw_rate(30.0, None), 0.0);
+        assert_eq!(coordination.yaw_rate(0.0, Some(20.0)), 0.0);
+        assert!((coordination.yaw_rate(30.0, Some(20.0)) - 16.22).abs() < 0.01);
+        assert!((coordination.yaw_rate(-30.0, Some(20.0)) + 16.22).abs() < 0.01);
+        // Should use minimum speed on low speed
+        assert!((coordination.yaw_rate(30.0, Some(5.0)) - 32.44).abs() < 0.01);
+
+        let coordination = TurnCoordination::new(&config, Configuration::FlyingWing);
+        assert!(!coordination.enabled());
+        assert_eq!(coordination.yaw_rate(30.0, Some(20.0)), 0.0);
+    }
+}
@@ -2,12 +2,16 @@
 use micromath::F32Ext;
 use nalgebra::Vector3;
 
-use crate::types::measurement::Attitude;
+use crate::{config::fcs::Envelop as Config, types::measurement::Attitude};
+
+const SPEED_GAIN: f32 = 2.0; // degree per second of pitch rate per m/s out of range
 
 pub struct Envelop {
     max_roll: f32,
     min_pitch: f32,
     max_pitch: f32,
+    min_speed: f32,
+    max_speed: f32,
     prev_roll_command: f32,
     hold_bank_angle: f32,
 }
@@ -15,14 +19,46 @@ pub struct Envelop {
 type Control = Vector3<f32>;
 
 impl Envelop {
-    pub fn new() -> Self {
-        let config = crate::config::get().fcs.envelop;
+    pub fn new(config: &Config) -> Self {
         let max_roll = config.max_roll as f32;
         let (min_pitch, max_pitch) = (config.min_pitch as f32, config.max_pitch as f32);
-        Self { max_roll, min_pitch, max_pitch, prev_roll_command: 0.0, hold_bank_angle: 0.0 }
+        let stall_speed = config.stall_speed as f32;
+        let min_speed = stall_speed * (100 + config.stall_margin as u16) as f32 / 100.0;
+        let max_speed = config.max_speed as f32;
+        Self {
+            max_roll,
+            min_pitch,
+            max_pitch,
+            min_speed,
+            max_speed,
+            prev_roll_command: 0.0,
+            hold_bank_angle: 0.0,
+        }
+    }
+
+    /// Lowers nose and blocks pitch-up below minimum speed,
+    /// raises nose and blocks pitch-down above maximum speed
+    fn protect_speed(&self, pitch: f32, atti: Attitude, speed: f32) -> f32 {
+        if self.min_speed > 0.0 && speed < self.min_speed {
+            let deficit = (self.min_speed - speed) * SPEED_GAIN;
+            let nose_down = if atti.pitch > self.min_pitch { deficit.min(10.0) } else { 0.0 };
+            return pitch.min(-nose_down);
+        }
+        if self.max_speed > 0.0 && speed > self.max_speed {
+            let excess = (speed - self.max_speed) * SPEED_GAIN;
+            let nose_up = if atti.pitch < self.max_pitch { excess.min(10.0) } else { 0.0 };
+            return pitch.max(nose_up);
+        }
+        pitch
     }
 
-    pub fn restrict(&mut self, input: Control, atti: Attitude, g: f32) -> Control {
+    pub fn restrict(
+        &mut self,
+        input: Control,
+        atti: Attitude,
+        g: f32,
+        speed: Option<f32>,
+    ) -> Control {
         let mut output = input;
 
         if input.x.abs() < 0.1 && self.prev_roll_command.abs() > 0.1 {
@@ -36,6 +72,9 @@ impl Envelop {
             _ if input.y.abs() < 0.1 => ((1.0 - g) * 100.0).clamp(-10.0, 10.0),
             _ => input.y,
         };
+        if let Some(speed) = speed {
+            output.y = self.protect_speed(output.y, atti, speed);
+        }
 
         if atti.roll.abs() > self.max_roll {
             output.x = -f32::min(atti.roll.abs() - self.max_roll, 25.0).copysign(atti.roll);
@@ -57,47 +96,45 @@ mod test {
 
         use crate::types::measurement::Attitude;
 
-        crate::config::reset();
-
-        let mut envelop = super::Envelop::new();
+        let mut envelop = super::Envelop::new(&Default::default());
         let input = Vector3::new(0.0, 0.0, 0.0);
         let attitude = Attitude::new(0.0, 0.0, 0.0);
-        assert_eq!(envelop.restrict(input, attitude, 1.0), Vector3::new(0.0, 0.0, 0.0));
+        assert_eq!(envelop.restrict(input, attitude, 1.0, None), Vector3::new(0.0, 0.0, 0.0));
 
         // Should reduce bank angle
         let attitude = Attitude::new(10.0, 0.0, 0.0);
-        assert_eq!(envelop.restrict(input, attitude, 1.0), Vector3::new(-10.0, 0.0, 0.0));
+        assert_eq!(envelop.restrict(input, attitude, 1.0, None), Vector3::new(-10.0, 0.0, 0.0));
 
         // Should not reduce bank angle since rolling
         let input = Vector3::new(10.0, 0.0, 0.0);
-        assert_eq!(envelop.restrict(input, attitude, 1.0), Vector3::new(10.0, 0.0, 0.0));
+        assert_eq!(envelop.restrict(input, attitude, 1.0, None), Vector3::new(10.0, 0.0, 0.0));
 
         let input = Vector3::new(0.0, 0.0, 0.0);
-        envelop.restrict(input, attitude, 1.0);
+        envelop.restrict(input, attitude, 1.0, None);
 
         // Should keep bank angle at 10°
         let attitude = Attitude::new(11.0, 0.0, 0.0);
-        assert_eq!(envelop.restrict(input, attitude, 1.0), Vector3::new(-1.0, 0.0, 0.0));
+        assert_eq!(envelop.restrict(input, attitude, 1.0, None), Vector3::new(-1.0, 0.0, 0.0));
 
         // Should reduce bank angle to 33°
         let attitude = Attitude::new(35.0, 0.0, 0.0);
-        assert_eq!(envelop.restrict(input, attitude, 1.0), Vector3::new(-2.0, 0.0, 0.0));
+        assert_eq!(envelop.restrict(input, attitude, 1.0, None), Vector3::new(-2.0, 0.0, 0.0));
 
         // Should not output roll with pitch input
         let attitude = Attitude::new(35.0, 0.0, 0.0);
         let input = Vector3::new(0.0, 10.0, 0.0);
-        assert_eq!(envelop.restrict(input, attitude, 1.0), Vector3::new(0.0, 10.0, 0.0));
+        assert_eq!(envelop.restrict(input, attitude, 1.0, None), Vector3::new(0.0, 10.0, 0.0));
 
         // Exceeding maximum bank angle
         let input = Vector3::new(10.0, 0.0, 0.0);
         let attitude = Attitude::new(70.0, 0.0, 0.0);
-        assert_eq!(envelop.restrict(input, attitude, 1.0), Vector3::new(-3.0, 0.0, 0.0));
+        assert_eq!(envelop.restrict(input, attitude, 1.0, None), Vector3::new(-3.0, 0.0, 0.0));
 
         // Should not exceed 25°/s
         let attitude = Attitude::new(100.0, 0.0, 0.0);
-        assert_eq!(envelop.restrict(input, attitude, 1.0), Vector3::new(-25.0, 0.0, 0.0));
+        assert_eq!(envelop.restrict(input, attitude, 1.0, None), Vector3::new(-25.0, 0.0, 0.0));
         let attitude = Attitude::new(-100.0, 0.0, 0.0);
-        assert_eq!(envelop.restrict(input, attitude, 1.0), Vector3::new(25.0, 0.0, 0.0));
+        assert_eq!(envelop.restrict(input, attitude, 1.0, None), Vector3::new(25.0, 0.0, 0.0));
     }
 
     #[test]
@@ -106,29 +143,66 @@ mod test {
 
         use crate::types::measurement::Attitude;
 
-        crate::config::reset();
-
-        let mut envelop = super::Envelop::new();
+        let mut envelop = super::Envelop::new(&Default::default());
 
         // Should reduce pitch angle
         let input = Vector3::new(0.0, 0.0, 0.0);
         let attitude = Attitude::new(0.0, 35.0, 0.0);
-        assert_eq!(envelop.restrict(input, attitude, 1.0), Vector3::new(0.0, -5.0, 0.0));
+        assert_eq!(envelop.restrict(input, attitude, 1.0, None), Vector3::new(0.0, -5.0, 0.0));
 
         let input = Vector3::new(0.0, 10.0, 0.0);
-        assert_eq!(envelop.restrict(input, attitude, 1.0), Vector3::new(0.0, -5.0, 0.0));
+        assert_eq!(envelop.restrict(input, attitude, 1.0, None), Vector3::new(0.0, -5.0, 0.0));
 
         // Should maintain constant G without input
         let input = Vector3::new(0.0, 0.0, 0.0);
         let attitude = Attitude::new(0.0, 0.0, 0.0);
-        assert_eq!(envelop.restrict(input, attitude, 1.1), Vector3::new(0.0, -10.0, 0.0));
-        assert_eq!(envelop.restrict(input, attitude, 1.2), Vector3::new(0.0, -10.0, 0.0));
-        assert_eq!(envelop.restrict(input, attitude, 0.9), Vector3::new(0.0, 10.0, 0.0));
-        assert_eq!(envelop.restrict(input, attitude, -0.1), Vector3::new(0.0, 10.0, 0.0));
+        assert_eq!(envelop.restrict(input, attitude, 1.1, None), Vector3::new(0.0, -10.0, 0.0));
+        assert_eq!(envelop.restrict(input, attitude, 1.2, None), Vector3::new(0.0, -10.0, 0.0));
+        assert_eq!(envelop.restrict(input, attitude, 0.9, None), Vector3::new(0.0, 10.0, 0.0));
+        assert_eq!(envelop.restrict(input, attitude, -0.1, None), Vector3::new(0.0, 10.0, 0.0));
 
         // Override pitch command with high pitch attitude
         let input = Vector3::new(0.0, 10.0, 0.0);
         let attitude = Attitude::new(0.0, 40.0, 0.0);
-        assert_eq!(envelop.restrict(input, attitude, 1.0), Vector3::new(0.0, -10.0, 0.0));
+        assert_eq!(envelop.restrict(input, attitude, 1.0, None), Vector3::new(0.0, -10.0, 0.0));
+    }
+
+    #[test]
+    fn test_envelop_speed() {
+        use nalgebra::Vector3;
+
+        use crate::{config::fcs::Envelop as Config, types::measurement::Attitude};
+
+        let config = Config { stall_speed: 10, max_speed: 40, ..Default::default() };
+        let mut envelop = super::Envelop::new(&config);
+        let input = Vector3::new(0.0, 10.0, 0.0);
+        let attitude = Attitude::new(0.0, 0.0, 0.0);
+        assert_eq!(envelop.restrict(input, attitude, 1.0, None), Vector3::new(0.0, 10.0, 0.0));
+        let output = envelop.restrict(input, attitude, 1.0, Some(20.0));
+        assert_eq!(output, Vector3::new(0.0, 10.0, 0.0));
+
+        // Should block pitch-up and lower nose near stall speed
+        let output = envelop.restrict(input, attitude, 1.0, Some(11.0));
+        assert_eq!(output, Vector3::new(0.0, -2.0, 0.0));
+        let output = envelop.restrict(input, attitude, 1.0, Some(5.0));
+        assert_eq!(output, Vector3::new(0.0, -10.0, 0.0));
+        let input = Vector3::new(0.0, -20.0, 0.0);
+        let output = envelop.restrict(input, attitude, 1.0, Some(11.0));
+        assert_eq!(output, Vector3::new(0.0, -20.0, 0.0));
+
+        // Should not lower nose further below minimum pitch
+        let attitude = Attitude::new(0.0, -16.0, 0.0);
+        let input = Vector3::new(0.0, 0.0, 0.0);
+        let output = envelop.restrict(input, attitude, 1.0, Some(11.0));
+        assert_eq!(output, Vector3::new(0.0, 0.0, 0.0));
+
+        // Should block pitch-down and raise nose above maximum speed
+        let attitude = Attitude::new(0.0, 0.0, 0.0);
+        let input = Vector3::new(0.0, -10.0, 0.0);
+        let output = envelop.restrict(input, attitude, 1.0, Some(42.0));
+        assert_eq!(output, Vector3::new(0.0, 4.0, 0.0));
+        let input = Vector3::new(0.0, 20.0, 0.0);
+        let output = envelop.restrict(input, attitude, 1.0, Some(42.0));
+        assert_eq!(output, Vector3::new(0.0, 20.0, 0.0));
     }
 }
@@ -0,0 +1,124 @@
+use fugit::NanosDurationU64 as Duration;
+
+use crate::{config::Failsafe as Config, ins::out::INS, types::control::Control};
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Stage {
+    Hold,
+    Climb,
+    Return,
+    Loiter,
+}
+
+pub struct Failsafe {
+    config: Config,
+    lost_since: Option<Duration>,
+    stage: Option<Stage>,
+}
+
+impl Failsafe {
+    pub fn new(config: &Config) -> Self {
+        Self { config: *config, lost_since: None, stage: None }
+    }
+
+    pub fn reconfigure(&mut self, config: &Config) {
+        self.config = *config;
+    }
+
+    /// Control older than timeout considered as link lost
+    pub fn timeout(&self) -> Duration {
+        Duration::millis(self.config.timeout as u64)
+    }
+
+    /// Throttle once hold time elapsed
+    pub fn throttle(&self) -> u16 {
+        (self.config.throttle as u32 * u16::MAX as u32 / 100) as u16
+    }
+
+    /// Link considered lost when control timed out, receiver reports failsafe or RSSI too low
+    pub fn is_link_lost(&self, control: Option<&Control>) -> bool {
+        match control {
+            Some(control) => control.failsafe || control.rssi < self.config.rssi_threshold as u16,
+            None => true,
+        }
+    }
+
+    /// Returns failsafe stage when link lost, return stage won't fallback to climb,
+    /// loiter around home once returned
+    pub fn update(&mut self, now: Duration, control: Option<&Control>, ins: &INS) -> Option<Stage> {
+        if !self.is_link_lost(control) {
+            self.lost_since = None;
+            self.stage = None;
+            return None;
+        }
+        let since = *self.lost_since.get_or_insert(now);
+        let height = ins.displacement.z().raw;
+        let (x, y) = (ins.displacement.x().raw, ins.displacement.y().raw);
+        let radius = self.config.loiter_radius as f32;
+        let returned = x * x + y * y <= radius * radius;
+        let stage = match self.stage {
+            _ if now < since + Duration::secs(self.config.hold_time as u64) => Stage::Hold,
+            Some(Stage::Loiter) => Stage::Loiter,
+            Some(Stage::Return) if returned => Stage::Loiter,
+            Some(Stage::Return) => Stage::Return,
+            _ if height < self.config.safe_altitude as f32 => Stage::Climb,
+            _ => Stage::Return,
+        };
+        self.stage = Some(stage);
+        self.stage
+    }
+}
+
+mod test {
+    #[test]
+    fn test_failsafe() {
+        use fugit::NanosDurationU64 as Duration;
+
+        use super::{Failsafe, Stage};
+        use crate::{config::Failsafe as Config, ins::out::INS, types::control::Control};
+
+        let mut failsafe = Failsafe::new(&Config::default());
+        let mut ins = INS::default();
+        let control = Control::default();
+        assert_eq!(failsafe.update(Duration::secs(1), Some(&control), &ins), None);
+        assert_eq!(failsafe.update(Duration::secs(2), None, &ins), Some(Stage::Hold));
+        assert_eq!(failsafe.update(Duration::secs(6), None, &ins), Some(Stage::Hold));
+        assert_eq!(failsafe.update(Duration::secs(7), None, &ins), Some(Stage::Climb));
+
+        ins.displacement.raw.x = 500.0;
+        ins.displacement.raw.z = 60.0;
+        assert_eq!(failsafe.update(Duration::secs(8), None, &ins), Some(Stage::Return));
+        ins.displacement.raw.z = 40.0;
+        assert_eq!(failsafe.update(Duration::secs(9), None, &ins), Some(Stage::Return));
+
+        // Should loiter around home once returned
+        ins.displacement.raw.x = 80.0;
+        assert_eq!(failsafe.update(Duration::secs(9), None, &ins), Some(Stage::Loiter));
+        ins.displacement.raw.x = 150.0;
+        assert_eq!(failsafe.update(Duration::secs(9), None, &ins), Some(Stage::Loiter));
+
+        // Should recover once link restored and start over
+        assert_eq!(failsafe.update(Duration::secs(10), Some(&control), &ins), None);
+        assert_eq!(failsafe.update(Duration::secs(11), None, &ins), Some(Stage::Hold));
+    }
+
+    #[test]
+    fn test_failsafe_trigger() {
+        use super::Failsafe;
+        use crate::{config::Failsafe as Config, types::control::Control};
+
+        let config = Config { rssi_threshold: 20, ..Default::default() };
+        let failsafe = Failsafe::new(&config);
+        let mut control = Control { rssi: 50, ..Default::default() };
+        assert!(!failsafe.is_link_lost(Some(&control)));
+        assert!(failsafe.is_link_lost(None));
+
+        control.rssi = 19;
+        assert!(failsafe.is_link_lost(Some(&control)));
+
+        control.rssi = 50;
+        control.failsafe = true;
+        assert!(failsafe.is_link_lost(Some(&control)));
+    }
+}
@@ -0,0 +1,132 @@
+use fugit::NanosDurationU64 as Duration;
+use nalgebra::Vector3;
+
+use super::autopilot::OVERRIDE_THRESHOLD;
+use crate::{
+    config::fcs::Launch as Config,
+    types::{control::Axes, measurement::Attitude},
+};
+
+const ATTITUDE_GAIN: f32 = 2.0; // degree per second per degree of attitude error
+const MAX_RATE: f32 = 30.0; // degree per second
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum State {
+    Idle,
+    Waiting,
+    Launching(Duration),
+    Done,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Output {
+    pub rates: Vector3<f32>,
+    pub throttle: u16,
+}
+
+/// Holds wings level at climb pitch after throw, then hands control back
+pub struct Launch {
+    config: Config,
+    state: State,
+}
+
+impl Launch {
+    pub fn new(config: &Config) -> Self {
+        Self { config: *config, state: State::Idle }
+    }
+
+    pub fn reconfigure(&mut self, config: &Config) {
+        self.config = *config;
+    }
+
+    fn rates(&self, attitude: Attitude) -> Vector3<f32> {
+        let roll = -attitude.roll * ATTITUDE_GAIN;
+        let pitch = (self.config.climb_pitch as f32 - attitude.pitch) * ATTITUDE_GAIN;
+        Vector3::new(roll.clamp(-MAX_RATE, MAX_RATE), pitch.clamp(-MAX_RATE, MAX_RATE), 0.0)
+    }
+
+    /// Waits for G spike once armed with motor stopped, motor spins up after delay,
+    /// ends after configured duration or on pilot roll or pitch input
+    pub fn update(
+        &mut self,
+        now: Duration,
+        armed: bool,
+        input: &Axes,
+        g: f32,
+        attitude: Attitude,
+    ) -> Option<Output> {
+        if !self.config.enable || !armed {
+            self.state = State::Idle;
+            return None;
+        }
+        let threshold: f32 = self.config.threshold.into();
+        let since = match self.state {
+            State::Idle | State::Waiting if g >= threshold => now,
+            State::Idle | State::Waiting => {
+                self.state = State::Waiting;
+                return Some(Output { rates: self.rates(attitude), throttle: 0 });
+            }
+            State::Launching(since) => since,
+            State::Done => return None,
+        };
+        self.state = State::Launching(since);
+        let overridden = input.roll.unsigned_abs().max(input.pitch.unsigned_abs());
+        if now >= since + Duration::secs(self.config.duration as u64)
+            || overridden >= OVERRIDE_THRESHOLD
+        {
+            self.state = State::Done;
+            return None;
+        }
+        let throttle = match () {
+            _ if now < since + Duration::millis(self.config.motor_delay as u64) => 0,
+            _ => (self.config.throttle as u32 * u16::MAX as u32 / 100) as u16,
+        };
+        Some(Output { rates: self.rates(attitude), throttle })
+    }
+}
+
+mod test {
+    #[test]
+    fn test_launch() {
+        use fugit::NanosDurationU64 as Duration;
+        use nalgebra::Vector3;
+
+        use super::{Launch, Output};
+        use crate::{
+            config::fcs::Launch as Config,
+            types::{control::Axes, measurement::Attitude},
+        };
+
+        let config = Config { enable: true, ..Default::default() };
+        let mut launch = Launch::new(&config);
+        let input = Axes::default();
+        let level = Attitude::new(0.0, 0.0, 0.0);
+        assert_eq!(launch.update(Duration::secs(1), false, &input, 1.0, level), None);
+
+        // Should hold motor until thrown
+        let rates = Vector3::new(0.0, 30.0, 0.0);
+        let expected = Some(Output { rates, throttle: 0 });
+        assert_eq!(launch.update(Duration::secs(2), true, &input, 1.0, level), expected);
+        assert_eq!(launch.update(Duration::secs(3), true, &input, 3.0, level), expected);
+        let attitude = Attitude::new(5.0, 10.0, 0.0);
+        let now = Duration::millis(3400);
+        let rates = Vector3::new(-10.0, 10.0, 0.0);
+        let expected = Some(Output { rates, throttle: 0 });
+        assert_eq!(launch.update(now, true, &input, 1.0, attitude), expected);
+
+        // Should spin up motor after delay
+        let now = Duration::millis(3500);
+        let expected = Some(Output { rates, throttle: u16::MAX });
+        assert_eq!(launch.update(now, true, &input, 1.0, attitude), expected);
+
+        // Should hand control back after duration
+        assert_eq!(launch.update(Duration::secs(8), true, &input, 1.0, attitude), None);
+        assert_eq!(launch.update(Duration::secs(9), true, &input, 3.0, attitude), None);
+
+        // Should hand control back on pilot input
+        assert_eq!(launch.update(Duration::secs(10), false, &input, 1.0, level), None);
+        assert!(launch.update(Duration::secs(11), true, &input, 3.0, level).is_some());
+        let input = Axes { pitch: i16::MAX / 2, ..Default::default() };
+        assert_eq!(launch.update(Duration::secs(12), true, &input, 1.0, level), None);
+    }
+}
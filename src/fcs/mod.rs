@@ -1,47 +1,104 @@
+pub mod arming;
+pub mod autopilot;
+pub mod autotune;
+pub mod coordination;
 pub mod envelop;
+pub mod failsafe;
+pub mod launch;
+pub mod multirotor;
 pub mod out;
 pub mod pid;
+pub mod tpa;
 
-use fugit::NanosDurationU64 as Duration;
 use nalgebra::Vector3;
 
-use crate::{algorithm::lpf::LPF, config::fcs::Configuration, datastore, types::control};
+use crate::{
+    algorithm::lpf::LPF,
+    config::{
+        self,
+        inputs::command::{Autopilot as Mode, Id},
+    },
+    datastore,
+    sys::jiffies,
+    types::control,
+};
 
 pub struct FCS {
-    interval: Duration,
-
     config_iteration: usize,
-    configuration: Configuration,
+    sample_rate: usize,
+    output: config::FCS,
     max_rates: Vector3<f32>,
     gyro_lpfs: [LPF<f32>; 3],
     pids: pid::PIDs,
+    tpa: tpa::TPA,
     envelop: envelop::Envelop,
+    coordination: coordination::TurnCoordination,
+    autopilot: autopilot::Autopilot,
+    autotune: autotune::Autotune,
+    launch: launch::Launch,
+    multirotor: multirotor::Multirotor,
+    failsafe: failsafe::Failsafe,
+    arming: arming::Arming,
+    throttle: u16, // last commanded
 }
 
 fn ratio(axis: i16) -> f32 {
     axis as f32 / i16::MAX as f32
 }
 
+/// Fixed wing steers fine at control input rate, while multirotor needs PID loop
+/// running close to gyro rate to stay stable, changing configuration takes effect after reboot
+pub fn update_rate(configuration: config::fcs::Configuration) -> usize {
+    if configuration.is_multirotor() { 500 } else { 50 }
+}
+
+fn max_rates(pids: &config::fcs::PIDs) -> Vector3<f32> {
+    Vector3::new(pids.roll.max_rate as f32, pids.pitch.max_rate as f32, pids.yaw.max_rate as f32)
+}
+
 impl FCS {
     fn reconfigure(&mut self) {
         let config = crate::config::get();
-        self.pids.reconfigure(&config.fcs.pids)
+        self.config_iteration = crate::config::iteration();
+        self.output = config.fcs.clone();
+        self.max_rates = max_rates(&config.fcs.pids);
+        self.envelop = envelop::Envelop::new(&config.fcs.envelop);
+        self.pids.reconfigure(&config.fcs.pids, self.sample_rate);
+        self.tpa.reconfigure(&config.fcs.tpa);
+        let configuration = config.fcs.configuration;
+        self.coordination.reconfigure(&config.fcs.turn_coordination, configuration);
+        self.autopilot.reconfigure(config);
+        self.launch.reconfigure(&config.fcs.launch);
+        self.multirotor.reconfigure(&config.fcs.multirotor, self.max_rates);
+        self.failsafe.reconfigure(&config.failsafe);
+        self.arming.reconfigure(config);
     }
 
+    /// Sample rate is how often update gets invoked, rather than IMU sample rate
     pub fn new(sample_rate: usize) -> Self {
-        let config = crate::config::get().fcs;
-        let max_roll = config.pids.roll.max_rate as f32;
-        let max_pitch = config.pids.pitch.max_rate as f32;
-        let max_yaw = config.pids.yaw.max_rate as f32;
+        let config = &crate::config::get().fcs;
+        let max_rates = max_rates(&config.pids);
         let lpf_freq: f32 = config.gyroscope.lpf.frequency.into();
         Self {
-            interval: Duration::micros(1000_000 / sample_rate as u64),
             config_iteration: crate::config::iteration(),
-            configuration: config.configuration,
-            max_rates: Vector3::new(max_roll, max_pitch, max_yaw),
+            sample_rate,
+            output: config.clone(),
+            max_rates,
             gyro_lpfs: [LPF::<f32>::new(sample_rate as f32, lpf_freq); 3],
-            pids: pid::PIDs::new(&crate::config::get().fcs.pids),
-            envelop: envelop::Envelop::new(),
+            pids: pid::PIDs::new(&config.pids, sample_rate),
+            tpa: tpa::TPA::new(&config.tpa),
+            envelop: envelop::Envelop::new(&config.envelop),
+            coordination: coordination::TurnCoordination::new(
+                &config.turn_coordination,
+                config.configuration,
+            ),
+            autopilot: autopilot::Autopilot::new(crate::config::get(), sample_rate),
+            autotune: autotune::Autotune::default(),
+            launch: launch::Launch::new(&config.launch),
+            multirotor: multirotor::Multirotor::new(&config.multirotor, max_rates),
+            failsafe: failsafe::Failsafe::new(&crate::config::get().failsafe),
+            arming: arming::Arming::new(crate::config::get()),
+            throttle: 0,
         }
     }
 
@@ -51,29 +108,95 @@ impl FCS {
         }
 
         let ds = datastore::acquire();
-        let input = ds.read_control_within(self.interval).unwrap_or_default().axes;
-        let mut axes = Vector3::new(
-            ratio(input.roll) * self.max_rates.x,
-            ratio(input.pitch) * self.max_rates.y,
-            ratio(input.yaw) * self.max_rates.z,
-        );
+        let control = ds.read_control_within(self.failsafe.timeout());
+        let ins = ds.read_ins();
+        let stage = self.failsafe.update(jiffies::get(), control.as_ref(), &ins);
+        let gnss_fixed = ds.read_gnss().fixed.is_some();
+        let sensors = arming::Sensors {
+            calibration: ds.read_calibration(),
+            voltage: ds.read_voltage(),
+            gnss_fixed,
+        };
+        let arming = self.arming.update(control.as_ref(), &sensors);
+        let control = control.unwrap_or_default();
+        let input = control.axes;
         let imu = ds.read_imu();
-        axes = self.envelop.restrict(axes, imu.attitude, imu.acceleration.g_force());
+        let multirotor = self.output.configuration.is_multirotor();
+        let axes = if multirotor {
+            self.multirotor.rates(&input, imu.attitude)
+        } else {
+            Vector3::new(
+                ratio(input.roll) * self.max_rates.x,
+                ratio(input.pitch) * self.max_rates.y,
+                ratio(input.yaw) * self.max_rates.z,
+            )
+        };
+        let armed = arming == arming::State::Armed;
+        let hold_throttle = if armed { self.throttle } else { 0 };
+        // Autopilot modes steer by banking and pitching, which suits fixed wing only,
+        // multirotor levels and holds throttle, then descends with failsafe throttle
+        let (mut axes, mut throttle) = match stage {
+            Some(stage) if multirotor => {
+                let throttle = match stage {
+                    failsafe::Stage::Hold => hold_throttle,
+                    _ => self.failsafe.throttle(),
+                };
+                (self.multirotor.level(imu.attitude), throttle)
+            }
+            Some(stage) => self.autopilot.failsafe(stage, &imu, &ins, hold_throttle),
+            None if multirotor => (axes, input.throttle),
+            None => self.autopilot.update(&control, axes, &imu, &ins),
+        };
+        ds.write_steerpoint(self.autopilot.navigation.steerpoint(&ins));
+        let speed = if gnss_fixed { Some(ins.velocity_vector.raw.norm()) } else { None };
+        if !multirotor {
+            let g = imu.acceleration.g_force();
+            axes = self.envelop.restrict(axes, imu.attitude, g, speed);
+            axes.z += self.coordination.yaw_rate(imu.attitude.roll, speed);
+            if let Some(launch) = self.launch.update(jiffies::get(), armed, &input, g, imu.attitude)
+            {
+                axes = launch.rates;
+                throttle = launch.throttle;
+            }
+        }
+        if stage.is_none() {
+            self.throttle = throttle;
+        }
         let mut gyro = imu.gyro;
         gyro.0.raw.iter_mut().enumerate().for_each(|(i, v)| *v = self.gyro_lpfs[i].filter(*v));
+        self.pids.scale(self.tpa.gain(speed, throttle));
         axes = self.pids.next_control(axes, gyro);
+        if stage.is_none() && control.contains(Id::Autopilot(Mode::Autotune)) {
+            let rates = Vector3::new(gyro.0.y().raw, gyro.0.x().raw, gyro.0.z().raw);
+            match self.autotune.update(jiffies::get(), &input, rates) {
+                autotune::Status::Tuning(axis, output) => axes[axis] = output,
+                autotune::Status::Done(gains) => {
+                    let mut config = crate::config::get().clone();
+                    autotune::apply(&mut config.fcs.pids, &gains, self.sample_rate as f32);
+                    crate::config::replace(&config);
+                }
+                autotune::Status::Idle => (),
+            }
+        } else {
+            self.autotune.disengage();
+        }
         let mut output = control::Axes {
-            throttle: input.throttle,
+            throttle,
             roll: (axes.x * i16::MAX as f32) as i16,
             pitch: (axes.y * i16::MAX as f32) as i16,
             yaw: (axes.z * i16::MAX as f32) as i16,
+            flaps: input.flaps,
         };
-        if input.yaw.is_positive() != output.yaw.is_positive() || input.yaw == 0 {
+        let opposite = input.yaw.is_positive() != output.yaw.is_positive();
+        if !multirotor && !self.coordination.enabled() && (opposite || input.yaw == 0) {
             output.yaw = output.yaw.clamp(-i16::MAX / 10, i16::MAX / 10);
         }
         ds.write_fcs(out::FCS {
             output: axes,
-            control: out::Configuration::from(output, self.configuration),
+            failsafe: stage,
+            autoland: self.autopilot.autoland.phase(),
+            arming,
+            control: out::Configuration::from(output, &self.output),
         });
     }
 }
@@ -0,0 +1,102 @@
+use nalgebra::Vector3;
+
+use crate::{
+    config::fcs::{Mode, Multirotor as Config},
+    types::{control::Axes, measurement::Attitude},
+};
+
+pub struct Multirotor {
+    config: Config,
+    max_rates: Vector3<f32>,
+}
+
+fn ratio(axis: i16) -> f32 {
+    axis as f32 / i16::MAX as f32
+}
+
+impl Multirotor {
+    pub fn new(config: &Config, max_rates: Vector3<f32>) -> Self {
+        Self { config: *config, max_rates }
+    }
+
+    pub fn reconfigure(&mut self, config: &Config, max_rates: Vector3<f32>) {
+        self.config = *config;
+        self.max_rates = max_rates;
+    }
+
+    /// Rate setpoints from pilot input, in angle mode roll and pitch sticks
+    /// command attitude and return to level when released
+    pub fn rates(&self, input: &Axes, attitude: Attitude) -> Vector3<f32> {
+        let yaw = ratio(input.yaw) * self.max_rates.z;
+        if self.config.mode == Mode::Rate {
+            let (roll, pitch) = (ratio(input.roll), ratio(input.pitch));
+            return Vector3::new(roll * self.max_rates.x, pitch * self.max_rates.y, yaw);
+        }
+        let max_angle = self.config.max_angle as f32;
+        let (roll, pitch) = (ratio(input.roll) * max_angle, ratio(input.pitch) * max_angle);
+        self.angle_rates(roll, pitch, attitude) + Vector3::new(0.0, 0.0, yaw)
+    }
+
+    /// Rate setpoints returning to level regardless of mode, used by failsafe
+    pub fn level(&self, attitude: Attitude) -> Vector3<f32> {
+        self.angle_rates(0.0, 0.0, attitude)
+    }
+
+    fn angle_rates(&self, roll: f32, pitch: f32, attitude: Attitude) -> Vector3<f32> {
+        let gain: f32 = self.config.level_gain.into();
+        let roll = (roll - attitude.roll) * gain;
+        let pitch = (pitch - attitude.pitch) * gain;
+        Vector3::new(
+            roll.clamp(-self.max_rates.x, self.max_rates.x),
+            pitch.clamp(-self.max_rates.y, self.max_rates.y),
+            0.0,
+        )
+    }
+}
+
+mod test {
+    #[test]
+    fn test_angle_mode() {
+        use nalgebra::Vector3;
+
+        use super::Multirotor;
+        use crate::{
+            config::fcs::Multirotor as Config,
+            types::{control::Axes, measurement::Attitude},
+        };
+
+        let multirotor = Multirotor::new(&Config::default(), Vector3::new(180.0, 180.0, 90.0));
+        let attitude = Attitude { roll: 10.0, pitch: -2.0, yaw: 0.0 };
+        let rates = multirotor.rates(&Axes::default(), attitude);
+        assert_eq!(rates, Vector3::new(-50.0, 10.0, 0.0));
+
+        let input = Axes { roll: i16::MAX, yaw: i16::MAX, ..Default::default() };
+        let rates = multirotor.rates(&input, Attitude::default());
+        assert_eq!(rates, Vector3::new(150.0, 0.0, 90.0));
+
+        let attitude = Attitude { roll: -30.0, pitch: 0.0, yaw: 0.0 };
+        let rates = multirotor.rates(&input, attitude);
+        assert_eq!(rates, Vector3::new(180.0, 0.0, 90.0));
+    }
+
+    #[test]
+    fn test_rate_mode() {
+        use nalgebra::Vector3;
+
+        use super::Multirotor;
+        use crate::{
+            config::fcs::{Mode, Multirotor as Config},
+            types::{control::Axes, measurement::Attitude},
+        };
+
+        let config = Config { mode: Mode::Rate, ..Default::default() };
+        let multirotor = Multirotor::new(&config, Vector3::new(180.0, 180.0, 90.0));
+        let attitude = Attitude { roll: 10.0, pitch: -2.0, yaw: 0.0 };
+        let input = Axes { roll: i16::MAX, pitch: -i16::MAX, ..Default::default() };
+        let rates = multirotor.rates(&input, attitude);
+        assert_eq!(rates, Vector3::new(180.0, -180.0, 0.0));
+
+        // Should level in failsafe even in rate mode
+        assert_eq!(multirotor.level(attitude), Vector3::new(-50.0, 10.0, 0.0));
+    }
+}
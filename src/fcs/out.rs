@@ -3,14 +3,27 @@ use nalgebra::Vector3;
 use serde::ser::SerializeMap;
 
 use crate::{
-    config::{fcs::Configuration as Config, peripherals::pwm::ServoType},
+    config::{
+        fcs::{Configuration as Config, Engines, Flaps, FCS as FCSConfig},
+        mixer::{Mixer, Output},
+        peripherals::pwm::ServoType,
+    },
+    fcs::{arming::State, autopilot::autoland::Phase, failsafe::Stage},
     types::control,
 };
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug)]
 pub struct FixedWing {
-    pub engines: [u16; 1],
-    pub control_surface: Vec<(ServoType, i16), 4>,
+    pub engines: Vec<u16, 4>,
+    pub control_surface: Vec<(ServoType, i16), 8>,
+}
+
+impl Default for FixedWing {
+    fn default() -> Self {
+        let mut engines = Vec::new();
+        engines.push(0).ok();
+        Self { engines, control_surface: Vec::new() }
+    }
 }
 
 impl serde::Serialize for FixedWing {
@@ -25,14 +38,15 @@ impl serde::Serialize for FixedWing {
 }
 
 impl FixedWing {
-    fn from(axes: control::Axes, config: Config) -> Self {
-        let mut control_surface: Vec<(ServoType, i16), 4> = Vec::new();
-        match config {
+    fn from(axes: control::Axes, config: &FCSConfig) -> Self {
+        let mut control_surface: Vec<(ServoType, i16), 8> = Vec::new();
+        match config.configuration {
             Config::Airplane => {
                 control_surface.push((ServoType::AileronLeft, -axes.roll)).ok();
                 control_surface.push((ServoType::AileronRight, axes.roll)).ok();
                 control_surface.push((ServoType::Elevator, axes.pitch)).ok();
                 control_surface.push((ServoType::Rudder, axes.yaw)).ok();
+                Self::flaps(axes, config.flaps, &mut control_surface);
             }
             Config::FlyingWing => {
                 control_surface.push((ServoType::ElevonLeft, -axes.roll + axes.pitch)).ok();
@@ -45,21 +59,155 @@ impl FixedWing {
                 control_surface.push((ServoType::RuddervatorLeft, value)).ok();
                 let value = -axes.yaw + axes.pitch;
                 control_surface.push((ServoType::RuddervatorRight, value)).ok();
+                Self::flaps(axes, config.flaps, &mut control_surface);
+            }
+            _ => (),
+        }
+        Self { engines: Self::engines(axes, config.engines), control_surface }
+    }
+
+    /// Flaps deflect down as negative value, flaperons act as ailerons drooped by flaps
+    fn flaps(axes: control::Axes, config: Flaps, control_surface: &mut Vec<(ServoType, i16), 8>) {
+        let ratio = axes.flaps as f32 / u16::MAX as f32;
+        let flap = -(ratio * config.max_angle as f32 / 90.0 * i16::MAX as f32) as i32;
+        control_surface.push((ServoType::FlapLeft, flap as i16)).ok();
+        control_surface.push((ServoType::FlapRight, flap as i16)).ok();
+        let droop = flap * config.flaperon as i32 / 100;
+        let saturate = |v: i32| v.clamp(-i16::MAX as i32, i16::MAX as i32) as i16;
+        control_surface.push((ServoType::FlaperonLeft, saturate(droop - axes.roll as i32))).ok();
+        control_surface.push((ServoType::FlaperonRight, saturate(droop + axes.roll as i32))).ok();
+    }
+
+    /// Yaw mixed into engines on left and right half, center engine takes throttle only
+    fn engines(axes: control::Axes, config: Engines) -> Vec<u16, 4> {
+        let count = config.count as usize;
+        let throttle = axes.throttle as i32;
+        let mut differential = axes.yaw as i32 * config.differential_thrust as i32 / 100;
+        if throttle == 0 {
+            differential = 0;
+        }
+        let mut engines: Vec<u16, 4> = Vec::new();
+        for i in 0..count {
+            let value = match i {
+                _ if i < count / 2 => throttle + differential,
+                _ if i >= count - count / 2 => throttle - differential,
+                _ => throttle,
+            };
+            engines.push(value.clamp(0, u16::MAX as i32) as u16).ok();
+        }
+        engines
+    }
+
+    fn custom(axes: control::Axes, mixer: &Mixer) -> Self {
+        let (roll, pitch, yaw) = (ratio(axes.roll), ratio(axes.pitch), ratio(axes.yaw));
+        let throttle = axes.throttle as f32 / u16::MAX as f32;
+        let mut fixed_wing = Self { engines: Vec::new(), control_surface: Vec::new() };
+        for rule in mixer.0.iter() {
+            let mut value = roll * rule.roll as f32 + pitch * rule.pitch as f32;
+            value += yaw * rule.yaw as f32 + throttle * rule.throttle as f32;
+            let value = value.clamp(rule.min as f32, rule.max as f32) / 100.0;
+            match rule.output {
+                Output::Motor(index) => {
+                    let index = index as usize;
+                    let engines = &mut fixed_wing.engines;
+                    while engines.len() <= index && engines.push(0).is_ok() {}
+                    if let Some(engine) = engines.get_mut(index) {
+                        *engine = (value.clamp(0.0, 1.0) * u16::MAX as f32) as u16;
+                    }
+                }
+                Output::Servo(servo_type) => {
+                    let value = (value * i16::MAX as f32) as i16;
+                    fixed_wing.control_surface.push((servo_type, value)).ok();
+                }
             }
         }
-        Self { engines: [axes.throttle; 1], control_surface }
+        fixed_wing
     }
 }
 
+// Motor factors of roll, pitch and yaw, positive roll rolls right,
+// positive pitch pitches up and positive yaw yaws right
+type MotorMix = [[f32; 3]];
+
+// Rear right, front right, rear left, front left
+const QUAD_X: [[f32; 3]; 4] =
+    [[-1.0, -1.0, -1.0], [-1.0, 1.0, 1.0], [1.0, -1.0, 1.0], [1.0, 1.0, -1.0]];
+
+// Rear, right, left, front
+const QUAD_PLUS: [[f32; 3]; 4] =
+    [[0.0, -1.0, -1.0], [-1.0, 0.0, 1.0], [1.0, 0.0, 1.0], [0.0, 1.0, -1.0]];
+
+// Rear right, front right, rear left, front left, right, left
+const HEX_X: [[f32; 3]; 6] = [
+    [-0.5, -0.866025, 1.0],
+    [-0.5, 0.866025, 1.0],
+    [0.5, -0.866025, -1.0],
+    [0.5, 0.866025, -1.0],
+    [-1.0, 0.0, -1.0],
+    [1.0, 0.0, 1.0],
+];
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Multirotor {
+    pub motors: Vec<u16, 8>,
+}
+
+impl Multirotor {
+    fn from(axes: control::Axes, config: Config) -> Self {
+        let mixer: &MotorMix = match config {
+            Config::QuadPlus => &QUAD_PLUS,
+            Config::HexX => &HEX_X,
+            _ => &QUAD_X,
+        };
+        let mut motors: Vec<u16, 8> = Vec::new();
+        if axes.throttle == 0 {
+            motors.resize(mixer.len(), 0).ok();
+            return Self { motors };
+        }
+        let (roll, pitch, yaw) = (ratio(axes.roll), ratio(axes.pitch), ratio(axes.yaw));
+        let mut mixes: Vec<f32, 8> = Vec::new();
+        for [r, p, y] in mixer.iter() {
+            mixes.push(roll * r + pitch * p + yaw * y).ok();
+        }
+        let mut min = mixes.iter().fold(f32::MAX, |a, &b| a.min(b));
+        let mut max = mixes.iter().fold(f32::MIN, |a, &b| a.max(b));
+        // Scale down attitude correction when exceeding throttle range
+        if max - min > 1.0 {
+            let scale = max - min;
+            mixes.iter_mut().for_each(|m| *m /= scale);
+            min /= scale;
+            max /= scale;
+        }
+        // Shift throttle so that attitude correction is preserved
+        let throttle = (axes.throttle as f32 / u16::MAX as f32).clamp(-min, 1.0 - max);
+        for mix in mixes.iter() {
+            let value = ((throttle + mix) * u16::MAX as f32).clamp(0.0, u16::MAX as f32);
+            motors.push(value as u16).ok();
+        }
+        Self { motors }
+    }
+}
+
+fn ratio(axis: i16) -> f32 {
+    axis as f32 / i16::MAX as f32
+}
+
 #[derive(Clone, Debug, Serialize)]
 #[serde(untagged)]
 pub enum Configuration {
     FixedWing(FixedWing),
+    Multirotor(Multirotor),
 }
 
 impl Configuration {
-    pub fn from(axes: control::Axes, config: Config) -> Self {
-        Self::FixedWing(FixedWing::from(axes, config))
+    pub fn from(axes: control::Axes, config: &FCSConfig) -> Self {
+        match config.configuration {
+            Config::QuadX | Config::QuadPlus | Config::HexX => {
+                Self::Multirotor(Multirotor::from(axes, config.configuration))
+            }
+            Config::Custom => Self::FixedWing(FixedWing::custom(axes, &config.mixer)),
+            _ => Self::FixedWing(FixedWing::from(axes, config)),
+        }
     }
 }
 
@@ -72,5 +220,124 @@ impl Default for Configuration {
 #[derive(Clone, Debug, Default, Serialize)]
 pub struct FCS {
     pub output: Vector3<f32>,
+    pub failsafe: Option<Stage>,
+    pub autoland: Option<Phase>,
+    pub arming: State,
     pub control: Configuration,
 }
+
+mod test {
+    #[test]
+    fn test_quad_x_mixer() {
+        use super::Multirotor;
+        use crate::{config::fcs::Configuration as Config, types::control::Axes};
+
+        let axes = Axes { throttle: u16::MAX / 2, ..Default::default() };
+        let motors = Multirotor::from(axes, Config::QuadX).motors;
+        assert_eq!(motors.as_slice(), &[32767; 4]);
+
+        // Roll right should speed up left motors
+        let axes = Axes { throttle: u16::MAX / 2, roll: i16::MAX / 4, ..Default::default() };
+        let motors = Multirotor::from(axes, Config::QuadX).motors;
+        assert_eq!(motors.as_slice(), &[16384, 16384, 49149, 49149]);
+
+        // Should keep roll authority at low throttle
+        let axes = Axes { throttle: 0x100, roll: i16::MAX / 4, ..Default::default() };
+        let motors = Multirotor::from(axes, Config::QuadX).motors;
+        assert_eq!(motors.as_slice(), &[0, 0, 32764, 32764]);
+
+        let axes = Axes { roll: i16::MAX, ..Default::default() };
+        let motors = Multirotor::from(axes, Config::HexX).motors;
+        assert_eq!(motors.as_slice(), &[0; 6]);
+    }
+
+    #[test]
+    fn test_multirotor_saturation() {
+        use super::Multirotor;
+        use crate::{config::fcs::Configuration as Config, types::control::Axes};
+
+        let axes = Axes { throttle: u16::MAX, pitch: i16::MAX, ..Default::default() };
+        let motors = Multirotor::from(axes, Config::QuadPlus).motors;
+        assert_eq!(motors.as_slice(), &[0, 32767, 32767, 65535]);
+    }
+
+    #[test]
+    fn test_custom_mixer() {
+        use super::FixedWing;
+        use crate::{
+            config::{
+                mixer::{Mixer, Output, Rule},
+                peripherals::pwm::ServoType,
+            },
+            types::control::Axes,
+        };
+
+        let rule = |output, pitch, yaw, throttle, min, max| Rule {
+            output,
+            roll: 0,
+            pitch,
+            yaw,
+            throttle,
+            min,
+            max,
+        };
+        let mut mixer = Mixer::default();
+        mixer.0.push(rule(Output::Motor(1), 0, 50, 100, 0, 100)).ok();
+        mixer.0.push(rule(Output::Motor(0), 0, -50, 100, 0, 100)).ok();
+        mixer.0.push(rule(Output::Servo(ServoType::Elevator), -100, 0, 0, -100, 50)).ok();
+
+        let axes = Axes {
+            throttle: u16::MAX / 2,
+            pitch: -i16::MAX,
+            yaw: i16::MAX / 2,
+            ..Default::default()
+        };
+        let fixed_wing = FixedWing::custom(axes, &mixer);
+        assert_eq!(fixed_wing.engines.as_slice(), &[16383, 49150]);
+        assert_eq!(fixed_wing.control_surface.as_slice(), &[(ServoType::Elevator, 16383)]);
+    }
+
+    #[test]
+    fn test_differential_thrust() {
+        use super::FixedWing;
+        use crate::{config::fcs::Engines, types::control::Axes};
+
+        let config = Engines { count: 2, differential_thrust: 50 };
+        let axes = Axes { throttle: 30000, yaw: 10000, ..Default::default() };
+        assert_eq!(FixedWing::engines(axes, config).as_slice(), &[35000, 25000]);
+
+        let axes = Axes { throttle: 60000, yaw: -20000, ..Default::default() };
+        assert_eq!(FixedWing::engines(axes, config).as_slice(), &[50000, 65535]);
+
+        let axes = Axes { yaw: 20000, ..Default::default() };
+        assert_eq!(FixedWing::engines(axes, config).as_slice(), &[0, 0]);
+
+        let config = Engines { count: 3, differential_thrust: 100 };
+        let axes = Axes { throttle: 30000, yaw: 10000, ..Default::default() };
+        assert_eq!(FixedWing::engines(axes, config).as_slice(), &[40000, 30000, 20000]);
+    }
+
+    #[test]
+    fn test_flaps() {
+        use super::FixedWing;
+        use crate::{
+            config::{fcs::Flaps, peripherals::pwm::ServoType, FCS},
+            types::control::Axes,
+        };
+
+        let config = FCS { flaps: Flaps { max_angle: 45, flaperon: 50 }, ..Default::default() };
+        let axes = Axes { roll: i16::MAX / 2, flaps: u16::MAX, ..Default::default() };
+        let fixed_wing = FixedWing::from(axes, &config);
+        let expected = [
+            (ServoType::AileronLeft, -16383),
+            (ServoType::AileronRight, 16383),
+            (ServoType::Elevator, 0),
+            (ServoType::Rudder, 0),
+            (ServoType::FlapLeft, -16383),
+            (ServoType::FlapRight, -16383),
+            (ServoType::FlaperonLeft, -24574),
+            (ServoType::FlaperonRight, 8192),
+        ];
+        assert_eq!(fixed_wing.control_surface.as_slice(), &expected);
+    }
+}
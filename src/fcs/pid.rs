@@ -1,39 +1,173 @@
 use nalgebra::Vector3;
 use pid::Pid;
 
-use crate::types::measurement::{unit::DEGs, Gyro};
+use crate::{
+    algorithm::lpf::LPF,
+    config::fcs::{PID as Config, PIDs as Configs},
+    types::measurement::{unit::DEGs, Gyro},
+};
+
+const SETPOINT_LPF_FREQUENCY: f32 = 15.0; // hz
+
+/// P and I by pid crate, D on measurement with dedicated LPF, plus feed-forward
+struct Axis {
+    config: Config,
+    pid: Pid<f32>,
+    kd: f32,
+    kff: f32,
+    d_lpf: Option<LPF<f32>>,
+    setpoint_lpf: LPF<f32>,
+    last_measurement: Option<f32>,
+}
+
+impl Axis {
+    fn new(config: &Config, sample_rate: f32) -> Self {
+        let (kp, ki) = (config.kp.into(), config.ki.into());
+        let (p_limit, i_limit) = (config.p_limit as f32, config.i_limit as f32);
+        let pid = Pid::new(kp, ki, 0.0, p_limit, i_limit, 0.0, 100.0, 0.0);
+        let d_lpf = match config.d_lpf {
+            0 => None,
+            freq => Some(LPF::<f32>::new(sample_rate, freq as f32)),
+        };
+        Self {
+            config: *config,
+            pid,
+            kd: config.kd.into(),
+            kff: config.kff.into(),
+            d_lpf,
+            setpoint_lpf: LPF::<f32>::new(sample_rate, SETPOINT_LPF_FREQUENCY),
+            last_measurement: None,
+        }
+    }
+
+    fn scale(&mut self, gain: f32) {
+        let config = &self.config;
+        let (kp, ki): (f32, f32) = (config.kp.into(), config.ki.into());
+        let (kd, kff): (f32, f32) = (config.kd.into(), config.kff.into());
+        self.pid.kp = kp * gain;
+        self.pid.ki = ki * gain;
+        self.kd = kd * gain;
+        self.kff = kff * gain;
+    }
+
+    /// Output in percentage of control-surface
+    fn next_control(&mut self, setpoint: f32, measurement: f32) -> f32 {
+        let ki = self.pid.ki;
+        let setpoint_change = (setpoint - self.setpoint_lpf.filter(setpoint)).abs();
+        if self.config.i_relax > 0 && setpoint_change > self.config.i_relax as f32 {
+            self.pid.ki = 0.0;
+        }
+        self.pid.setpoint = setpoint;
+        let output = self.pid.next_control_output(measurement).output;
+        self.pid.ki = ki;
+
+        let delta = measurement - self.last_measurement.unwrap_or(measurement);
+        self.last_measurement = Some(measurement);
+        let delta = self.d_lpf.as_mut().map(|lpf| lpf.filter(delta)).unwrap_or(delta);
+        let d_limit = self.config.d_limit as f32;
+        let d = (-delta * self.kd).clamp(-d_limit, d_limit);
+        (output + d + setpoint * self.kff).clamp(-100.0, 100.0)
+    }
+}
 
 pub struct PIDs {
-    roll: Pid<f32>,
-    pitch: Pid<f32>,
-    yaw: Pid<f32>,
+    roll: Axis,
+    pitch: Axis,
+    yaw: Axis,
 }
 
 impl PIDs {
-    fn config_to_pid(config: &crate::config::fcs::PID) -> Pid<f32> {
-        let (kp, ki, kd) = (config.kp.into(), config.ki.into(), config.kd.into());
-        Pid::new(kp, ki, kd, 100.0, 10.0, 10.0, 100.0, 0.0)
+    pub fn new(config: &Configs, sample_rate: usize) -> Self {
+        let sample_rate = sample_rate as f32;
+        let roll = Axis::new(&config.roll, sample_rate);
+        let pitch = Axis::new(&config.pitch, sample_rate);
+        let yaw = Axis::new(&config.yaw, sample_rate);
+        Self { roll, pitch, yaw }
     }
 
-    pub fn new(config: &crate::config::fcs::PIDs) -> Self {
-        let roll = Self::config_to_pid(&config.roll);
-        let pitch = Self::config_to_pid(&config.pitch);
-        let yaw = Self::config_to_pid(&config.yaw);
-        Self { roll, pitch, yaw }
+    pub fn reconfigure(&mut self, config: &Configs, sample_rate: usize) {
+        *self = Self::new(config, sample_rate);
     }
 
-    pub fn reconfigure(&mut self, config: &crate::config::fcs::PIDs) {
-        *self = Self::new(config);
+    /// Scales configured gains, e.g. attenuated by TPA
+    pub fn scale(&mut self, gain: f32) {
+        self.roll.scale(gain);
+        self.pitch.scale(gain);
+        self.yaw.scale(gain);
     }
 
     pub fn next_control(&mut self, control: Vector3<f32>, gyro: Gyro<DEGs>) -> Vector3<f32> {
-        self.roll.setpoint = control.x;
-        self.pitch.setpoint = control.y;
-        self.yaw.setpoint = control.z;
-
-        let roll = self.roll.next_control_output(gyro.0.y().raw).output / 100.0;
-        let pitch = self.pitch.next_control_output(gyro.0.x().raw).output / 100.0;
-        let yaw = self.yaw.next_control_output(gyro.0.z().raw).output / 100.0;
+        let roll = self.roll.next_control(control.x, gyro.0.y().raw) / 100.0;
+        let pitch = self.pitch.next_control(control.y, gyro.0.x().raw) / 100.0;
+        let yaw = self.yaw.next_control(control.z, gyro.0.z().raw) / 100.0;
         Vector3::new(roll, pitch, yaw)
     }
 }
+
+mod test {
+    #[test]
+    fn test_feed_forward() {
+        use super::Axis;
+        use crate::config::fcs::PID;
+
+        let config = PID { kff: fixed_point::fixed!(0.5), ..Default::default() };
+        let mut axis = Axis::new(&config, 100.0);
+        assert_eq!(axis.next_control(60.0, 0.0), 30.0);
+        assert_eq!(axis.next_control(300.0, 0.0), 100.0);
+    }
+
+    #[test]
+    fn test_limits() {
+        use super::Axis;
+        use crate::config::fcs::PID;
+
+        let config = PID {
+            kp: fixed_point::fixed!(1.0),
+            ki: fixed_point::fixed!(1.0),
+            kd: fixed_point::fixed!(10.0),
+            p_limit: 20,
+            i_limit: 5,
+            d_limit: 10,
+            ..Default::default()
+        };
+        let mut axis = Axis::new(&config, 100.0);
+        assert_eq!(axis.next_control(100.0, 0.0), 25.0);
+        // D-term on measurement opposes gyro rate change
+        assert_eq!(axis.next_control(100.0, 3.0), 15.0);
+    }
+
+    #[test]
+    fn test_i_relax() {
+        use super::Axis;
+        use crate::config::fcs::PID;
+
+        let config = PID { ki: fixed_point::fixed!(1.0), i_limit: 100, ..Default::default() };
+        let mut axis = Axis::new(&config, 100.0);
+        assert_eq!(axis.next_control(50.0, 0.0), 50.0);
+
+        let config = PID { i_relax: 20, ..config };
+        let mut axis = Axis::new(&config, 100.0);
+        assert_eq!(axis.next_control(50.0, 0.0), 0.0);
+        for _ in 0..100 {
+            axis.next_control(50.0, 50.0);
+        }
+        assert_eq!(axis.next_control(50.0, 40.0), 10.0);
+    }
+
+    #[test]
+    fn test_d_lpf() {
+        use super::Axis;
+        use crate::config::fcs::PID;
+
+        let config = PID { kd: fixed_point::fixed!(1.0), d_limit: 100, ..Default::default() };
+        let mut axis = Axis::new(&config, 1000.0);
+        axis.next_control(0.0, 0.0);
+        assert_eq!(axis.next_control(0.0, 10.0), -10.0);
+
+        let config = PID { d_lpf: 50, ..config };
+        let mut axis = Axis::new(&config, 1000.0);
+        axis.next_control(0.0, 0.0);
+        let output = axis.next_control(0.0, 10.0);
+        assert!(output < 0.0 && output > -10.0);
+    }
+}
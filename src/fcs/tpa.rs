@@ -0,0 +1,67 @@
+use crate::config::fcs::{Breakpoint, TPA as Config};
+
+pub struct TPA {
+    config: Config,
+}
+
+fn interpolate(table: &[Breakpoint], key: fn(&Breakpoint) -> f32, value: f32) -> f32 {
+    let gain = |breakpoint: &Breakpoint| breakpoint.gain as f32 / 100.0;
+    let (first, last) = match (table.first(), table.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return 1.0,
+    };
+    if value <= key(first) {
+        return gain(first);
+    }
+    for pair in table.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        if value <= key(b) {
+            let ratio = (value - key(a)) / (key(b) - key(a));
+            return gain(a) + (gain(b) - gain(a)) * ratio;
+        }
+    }
+    gain(last)
+}
+
+impl TPA {
+    pub fn new(config: &Config) -> Self {
+        Self { config: config.clone() }
+    }
+
+    pub fn reconfigure(&mut self, config: &Config) {
+        self.config = config.clone();
+    }
+
+    /// PID gain interpolated on speed when available, otherwise on throttle
+    pub fn gain(&self, speed: Option<f32>, throttle: u16) -> f32 {
+        match speed {
+            Some(speed) => interpolate(&self.config.0, |b| b.speed as f32, speed),
+            None => {
+                let throttle = throttle as f32 * 100.0 / u16::MAX as f32;
+                interpolate(&self.config.0, |b| b.throttle as f32, throttle)
+            }
+        }
+    }
+}
+
+mod test {
+    #[test]
+    fn test_tpa() {
+        use super::TPA;
+        use crate::config::fcs::{Breakpoint, TPA as Config};
+
+        let tpa = TPA::new(&Config::default());
+        assert_eq!(tpa.gain(Some(30.0), 0), 1.0);
+
+        let mut config = Config::default();
+        config.0.push(Breakpoint { speed: 10, throttle: 30, gain: 150 }).ok();
+        config.0.push(Breakpoint { speed: 20, throttle: 60, gain: 100 }).ok();
+        config.0.push(Breakpoint { speed: 35, throttle: 100, gain: 60 }).ok();
+        let tpa = TPA::new(&config);
+        assert_eq!(tpa.gain(Some(5.0), 0), 1.5);
+        assert_eq!(tpa.gain(Some(15.0), 0), 1.25);
+        assert_eq!(tpa.gain(Some(50.0), 0), 0.6);
+        assert!((tpa.gain(None, u16::MAX / 2) - 1.1667).abs() < 0.001);
+        assert_eq!(tpa.gain(None, u16::MAX), 0.6);
+    }
+}
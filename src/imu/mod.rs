@@ -1,4 +1,6 @@
+pub mod notch;
 pub mod out;
+pub mod rpm_notch;
 
 use fugit::NanosDurationU64 as Duration;
 use nalgebra::{UnitQuaternion, Vector3};
@@ -13,8 +15,9 @@ use crate::{
     },
 };
 
-#[derive(PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub enum CalibrationStatus {
+    #[default]
     Calibrating,
     Validating,
     Calibrated,
@@ -65,15 +68,26 @@ impl Calibration {
 }
 
 pub struct IMU {
+    config_iteration: usize,
     interval: Duration,
     ahrs: Mahony,
     calibration: Calibration,
     rotation: UnitQuaternion<f32>,
+    notch: notch::DynamicNotch,
+    rpm_notch: rpm_notch::RPMNotch,
 }
 
 impl IMU {
+    fn reconfigure(&mut self) {
+        self.config_iteration = config::iteration();
+        let gyroscope = &config::get().fcs.gyroscope;
+        self.notch.reconfigure(&gyroscope.notch);
+        self.rpm_notch.reconfigure(&gyroscope.rpm_notch);
+    }
+
     pub fn new(sample_rate: usize) -> Self {
         let config = config::get().imu;
+        let gyroscope = &config::get().fcs.gyroscope;
         let interval = Duration::micros(1000_000 / sample_rate as u64);
         let (kp, ki) = (config.mahony.kp.into(), config.mahony.ki.into());
         let calibration = Calibration {
@@ -94,29 +108,38 @@ impl IMU {
         let yaw: f32 = config.rotation.yaw.into();
         let euler = Euler::new(0.0, -pitch / DEGREE_PER_DAG, -yaw / DEGREE_PER_DAG);
         Self {
+            config_iteration: config::iteration(),
             interval,
             ahrs: Mahony::new(sample_rate as f32, kp, ki, config.magnetometer.declination.into()),
             calibration,
             rotation: euler.into(),
+            notch: notch::DynamicNotch::new(&gyroscope.notch, sample_rate),
+            rpm_notch: rpm_notch::RPMNotch::new(&gyroscope.rpm_notch, sample_rate),
         }
     }
 
     /// gyro x, y, z means spin around x, y and z axis, clock-wise is positive
     pub fn update(&mut self, acceleration: Vector3<f32>, gyro: Vector3<f32>) {
+        if self.config_iteration != config::iteration() {
+            self.reconfigure();
+        }
         let acceleration = self.rotation.transform_vector(&acceleration);
         let gyro = self.rotation.transform_vector(&gyro);
+        let ds = datastore::acquire();
         if self.calibration.status != CalibrationStatus::Calibrated {
             self.calibration.calibrate(gyro);
+            ds.write_calibration(self.calibration.status);
             return;
         }
-        let ds = datastore::acquire();
         let gnss = ds.read_gnss_within(self.interval);
         let heading = gnss.map(|g| g.fixed.map(|f| f.heading)).flatten().flatten();
         let magnetism = ds.read_magnetism_within(self.interval);
 
         let calib = &self.calibration.accelerometer;
         let raw_acceleration = (acceleration - calib.bias).component_mul(&calib.gain);
-        let raw_gyro = gyro - self.calibration.gyroscope_bias;
+        let rpms = ds.read_motor_rpm_within(Duration::millis(100)).unwrap_or_default();
+        let raw_gyro = self.rpm_notch.filter(gyro - self.calibration.gyroscope_bias, &rpms.0);
+        let raw_gyro = self.notch.filter(raw_gyro);
 
         let acceleration: Vector3<f32> = raw_acceleration.into();
         let gyro: Vector3<f32> = raw_gyro.into();
@@ -142,5 +165,6 @@ impl IMU {
     /// Testing only
     pub fn skip_calibration(&mut self) {
         self.calibration.status = CalibrationStatus::Calibrated;
+        datastore::acquire().write_calibration(self.calibration.status);
     }
 }
@@ -0,0 +1,137 @@
+use core::f32::consts::PI;
+
+#[cfg(not(any(test, feature = "std")))]
+use micromath::F32Ext;
+use nalgebra::Vector3;
+
+use crate::{
+    algorithm::{biquad::Biquad, fft::fft},
+    config::fcs::Notch as Config,
+};
+
+const FFT_SIZE: usize = 128;
+const SMOOTHING: f32 = 0.5; // weight of new peak frequency
+
+/// Notch filters retuned to gyro vibration peak found by FFT
+pub struct DynamicNotch {
+    config: Config,
+    sample_rate: f32,
+    window: [f32; FFT_SIZE],
+    samples: [[f32; FFT_SIZE]; 3],
+    index: usize,
+    frequencies: [f32; 3],
+    notches: [Biquad; 3],
+}
+
+impl DynamicNotch {
+    pub fn new(config: &Config, sample_rate: usize) -> Self {
+        let sample_rate = sample_rate as f32;
+        let mut window = [0.0; FFT_SIZE];
+        for (i, value) in window.iter_mut().enumerate() {
+            *value = 0.5 - 0.5 * (2.0 * PI * i as f32 / (FFT_SIZE - 1) as f32).cos();
+        }
+        let frequency = config.max_frequency.min(sample_rate as u16 / 2) as f32;
+        let q = config.q.into();
+        Self {
+            config: *config,
+            sample_rate,
+            window,
+            samples: [[0.0; FFT_SIZE]; 3],
+            index: 0,
+            frequencies: [frequency; 3],
+            notches: [Biquad::notch(sample_rate, frequency, q); 3],
+        }
+    }
+
+    pub fn reconfigure(&mut self, config: &Config) {
+        *self = Self::new(config, self.sample_rate as usize);
+    }
+
+    pub fn frequencies(&self) -> [f32; 3] {
+        self.frequencies
+    }
+
+    /// Strongest frequency within configured range, interpolated over neighbour bins
+    fn peak(&self, axis: usize) -> Option<f32> {
+        let samples = &self.samples[axis];
+        let mean = samples.iter().sum::<f32>() / FFT_SIZE as f32;
+        let mut real = [0.0; FFT_SIZE];
+        for (i, value) in real.iter_mut().enumerate() {
+            *value = (samples[i] - mean) * self.window[i];
+        }
+        let mut imag = [0.0; FFT_SIZE];
+        fft(&mut real, &mut imag);
+
+        let resolution = self.sample_rate / FFT_SIZE as f32;
+        let (min, max) = (self.config.min_frequency as f32, self.config.max_frequency as f32);
+        let min_bin = ((min / resolution) as usize).max(1);
+        let max_bin = ((max / resolution) as usize).min(FFT_SIZE / 2 - 1);
+        let power = |i: usize| real[i] * real[i] + imag[i] * imag[i];
+        let bin = (min_bin..=max_bin).max_by(|&a, &b| power(a).total_cmp(&power(b)))?;
+        if power(bin) <= 0.0 {
+            return None;
+        }
+        let (left, center, right) = (power(bin - 1), power(bin), power(bin + 1));
+        let offset = (right - left) / (left + center + right);
+        let frequency = (bin as f32 + offset) * resolution;
+        Some(frequency.max(min).min(max))
+    }
+
+    pub fn filter(&mut self, gyro: Vector3<f32>) -> Vector3<f32> {
+        if !self.config.enable {
+            return gyro;
+        }
+        for axis in 0..3 {
+            self.samples[axis][self.index] = gyro[axis];
+        }
+        self.index += 1;
+        if self.index == FFT_SIZE {
+            self.index = 0;
+            let q = self.config.q.into();
+            for axis in 0..3 {
+                if let Some(peak) = self.peak(axis) {
+                    let frequency = &mut self.frequencies[axis];
+                    *frequency += (peak - *frequency) * SMOOTHING;
+                    self.notches[axis].retune(self.sample_rate, *frequency, q);
+                }
+            }
+        }
+        let mut output = gyro;
+        for axis in 0..3 {
+            output[axis] = self.notches[axis].filter(gyro[axis]);
+        }
+        output
+    }
+}
+
+mod test {
+    #[test]
+    fn test_dynamic_notch() {
+        use core::f32::consts::PI;
+
+        use nalgebra::Vector3;
+
+        use super::DynamicNotch;
+        use crate::config::fcs::Notch as Config;
+
+        let config = Config { enable: true, ..Default::default() };
+        let mut notch = DynamicNotch::new(&config, 1000);
+        let mut peak = 0.0f32;
+        for i in 0..2000 {
+            let vibration = (2.0 * PI * 150.0 * i as f32 / 1000.0).sin() * 20.0;
+            let gyro = Vector3::new(vibration + 10.0, 0.0, 0.0);
+            let output = notch.filter(gyro);
+            if i >= 1800 {
+                peak = peak.max((output.x - 10.0).abs());
+            }
+        }
+        let frequencies = notch.frequencies();
+        assert!((frequencies[0] - 150.0).abs() < 5.0);
+        assert_eq!(frequencies[1], 400.0);
+        assert!(peak < 5.0);
+
+        let mut notch = DynamicNotch::new(&Config::default(), 1000);
+        let gyro = Vector3::new(1.0, 2.0, 3.0);
+        assert_eq!(notch.filter(gyro), gyro);
+    }
+}
@@ -0,0 +1,84 @@
+use nalgebra::Vector3;
+
+use crate::{algorithm::biquad::Biquad, config::fcs::RPMNotch as Config};
+
+const MAX_MOTORS: usize = 8;
+const RETUNE_THRESHOLD: f32 = 1.0; // Hz
+
+/// Notch filters on each motor rotation frequency
+pub struct RPMNotch {
+    config: Config,
+    sample_rate: f32,
+    frequencies: [f32; MAX_MOTORS],
+    notches: [[Biquad; 3]; MAX_MOTORS],
+}
+
+impl RPMNotch {
+    pub fn new(config: &Config, sample_rate: usize) -> Self {
+        Self {
+            config: *config,
+            sample_rate: sample_rate as f32,
+            frequencies: [0.0; MAX_MOTORS],
+            notches: [[Biquad::default(); 3]; MAX_MOTORS],
+        }
+    }
+
+    pub fn reconfigure(&mut self, config: &Config) {
+        *self = Self::new(config, self.sample_rate as usize);
+    }
+
+    /// Motors turning below minimum frequency are not filtered
+    pub fn filter(&mut self, gyro: Vector3<f32>, rpms: &[u32]) -> Vector3<f32> {
+        if !self.config.enable {
+            return gyro;
+        }
+        let (min, max) = (self.config.min_frequency as f32, self.sample_rate / 2.0);
+        let q = self.config.q.into();
+        let mut output = gyro;
+        for (i, &rpm) in rpms.iter().take(MAX_MOTORS).enumerate() {
+            let frequency = rpm as f32 / 60.0;
+            if frequency < min || frequency >= max {
+                continue;
+            }
+            let notches = &mut self.notches[i];
+            if (frequency - self.frequencies[i]).abs() >= RETUNE_THRESHOLD {
+                self.frequencies[i] = frequency;
+                notches.iter_mut().for_each(|n| n.retune(self.sample_rate, frequency, q));
+            }
+            for axis in 0..3 {
+                output[axis] = notches[axis].filter(output[axis]);
+            }
+        }
+        output
+    }
+}
+
+mod test {
+    #[test]
+    fn test_rpm_notch() {
+        use core::f32::consts::PI;
+
+        use nalgebra::Vector3;
+
+        use super::RPMNotch;
+        use crate::config::fcs::RPMNotch as Config;
+
+        let config = Config { enable: true, ..Default::default() };
+        let mut notch = RPMNotch::new(&config, 1000);
+        let mut peak = 0.0f32;
+        for i in 0..1000 {
+            let vibration = (2.0 * PI * 200.0 * i as f32 / 1000.0).sin() * 20.0;
+            let output = notch.filter(Vector3::new(vibration, 0.0, vibration), &[12000, 3000]);
+            if i >= 900 {
+                peak = peak.max(output.x.abs()).max(output.z.abs());
+            }
+        }
+        assert!(peak < 1.0);
+
+        // Should bypass motor below minimum frequency
+        let gyro = Vector3::new(1.0, 2.0, 3.0);
+        assert_eq!(notch.filter(gyro, &[3000]), gyro);
+        let mut notch = RPMNotch::new(&Default::default(), 1000);
+        assert_eq!(notch.filter(gyro, &[12000]), gyro);
+    }
+}
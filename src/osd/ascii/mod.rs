@@ -26,8 +26,8 @@ impl OSD {
         let mut mode = command::Mode::NAV;
         if let Some(control) = ds.read_control_within(Duration::millis(100)) {
             for command in control.commands.iter() {
-                match command {
-                    command::Id::Mode(m) => mode = *m,
+                if let command::Id::Mode(m) = command {
+                    mode = *m
                 }
             }
         }
@@ -9,6 +9,12 @@ use fixed_point::FixedPoint;
 use super::Frame;
 use crate::{
     collection, config, datastore,
+    fcs::{
+        arming::{Check, State},
+        autopilot::autoland::Phase,
+        failsafe::Stage,
+        out::FCS,
+    },
     types::{
         coordinate::SphericalCoordinate,
         measurement::{
@@ -23,6 +29,45 @@ pub type FrameConsumer<const W: usize, const H: usize> = fn(&Frame<W, H>);
 
 const INS_ALIGN: &str = "ALN";
 
+fn failsafe_note(stage: Option<Stage>) -> &'static str {
+    match stage {
+        Some(Stage::Hold) => "FAILSAFE",
+        Some(Stage::Climb) => "FAILSAFE CLB",
+        Some(Stage::Return) => "FAILSAFE RTH",
+        Some(Stage::Loiter) => "FAILSAFE LTR",
+        None => "",
+    }
+}
+
+fn autoland_note(phase: Option<Phase>) -> &'static str {
+    match phase {
+        Some(Phase::Approach) => "APPROACH",
+        Some(Phase::Glide) => "GLIDE",
+        Some(Phase::Flare) => "FLARE",
+        Some(Phase::Landed) => "LANDED",
+        None => "",
+    }
+}
+
+fn center_note(fcs: &FCS) -> &'static str {
+    match fcs.failsafe {
+        Some(stage) => failsafe_note(Some(stage)),
+        None => autoland_note(fcs.autoland),
+    }
+}
+
+fn arming_note(state: State) -> &'static str {
+    match state {
+        State::Blocked(Check::Calibration) => "CAL",
+        State::Blocked(Check::Throttle) => "THR",
+        State::Blocked(Check::Link) => "LNK",
+        State::Blocked(Check::Battery) => "BAT",
+        State::Blocked(Check::GNSS) => "GPS",
+        State::Disarmed => "DISARMED",
+        State::Armed => "",
+    }
+}
+
 pub struct NAV {
     hud: HUD,
 }
@@ -62,13 +107,14 @@ impl NAV {
         let data = collector.collect();
 
         let height = data.ins.displacement.z().u(Feet).raw as i16;
-        let delta = data.ins.displacement;
+        let steerpoint = &data.steerpoint;
+        let delta = (steerpoint.waypoint.position - data.ins.position).t(|v| v as f32);
         let vector = data.imu.quaternion.inverse_transform_vector(&delta.raw);
         let transformed = Displacement::from(vector, Meter, ENU);
         let coordinate: SphericalCoordinate<Meter> = (transformed * 10.0).into();
         let steerpoint = Steerpoint {
-            number: 0,
-            name: "HOME",
+            number: steerpoint.index,
+            name: steerpoint.waypoint.name.as_str(),
             heading: delta.azimuth(),
             coordinate: hud_coordinate(coordinate.u(NauticalMile)),
         };
@@ -100,7 +146,11 @@ impl NAV {
             heading: data.imu.attitude.yaw as u16,
             g_force: FixedPoint((data.imu.acceleration.g_force() * 10.0) as i8),
             height: if height > 200 { i16::MIN } else { height },
-            notes: Notes { left: note_left, center: "", right: "" },
+            notes: Notes {
+                left: note_left,
+                center: center_note(&data.fcs),
+                right: arming_note(data.fcs.arming),
+            },
             battery: data.voltage.soc(),
             rssi: data.control.rssi as u8,
             unit: Unit::Aviation,
@@ -114,6 +164,19 @@ impl NAV {
 }
 
 mod test {
+    #[test]
+    fn test_center_note() {
+        use super::center_note;
+        use crate::fcs::{autopilot::autoland::Phase, failsafe::Stage, out::FCS};
+
+        let mut fcs = FCS::default();
+        assert_eq!(center_note(&fcs), "");
+        fcs.autoland = Some(Phase::Flare);
+        assert_eq!(center_note(&fcs), "FLARE");
+        fcs.failsafe = Some(Stage::Return);
+        assert_eq!(center_note(&fcs), "FAILSAFE RTH");
+    }
+
     #[test]
     fn test_speed_vector() {
         use ascii_osd_hud::telemetry as hud;
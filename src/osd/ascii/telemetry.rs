@@ -59,7 +59,10 @@ impl Telemetry {
 
         match collection.fcs.control {
             Configuration::FixedWing(fixed_wing) => {
-                write!(buf, "ENG {:3}", percentage(fixed_wing.engines[0], u16::MAX)).ok();
+                write!(buf, "ENG").ok();
+                for &v in fixed_wing.engines.iter() {
+                    write!(buf, " {:3}", percentage(v, u16::MAX)).ok();
+                }
                 next_row!(frame, buf, row, H);
 
                 write!(buf, "CTL").ok();
@@ -67,9 +70,23 @@ impl Telemetry {
                     write!(buf, " {:4}", percentage(v, i16::MAX)).ok();
                 }
             }
+            Configuration::Multirotor(multirotor) => {
+                write!(buf, "MOT").ok();
+                for &v in multirotor.motors.iter() {
+                    write!(buf, " {:3}", percentage(v, u16::MAX)).ok();
+                }
+            }
         }
         next_row!(frame, buf, row, H);
 
+        if !collection.motor_rpm.0.is_empty() {
+            write!(buf, "RPM").ok();
+            for &rpm in collection.motor_rpm.0.iter() {
+                write!(buf, " {:5}", rpm).ok();
+            }
+            next_row!(frame, buf, row, H);
+        }
+
         write!(buf, "BAT {}v", collection.voltage.0).ok();
         next_row!(frame, buf, row, H);
 
@@ -81,11 +98,14 @@ impl Telemetry {
 
 mod test {
     #[test]
+    #[serial]
     fn test_telemetry() {
         use std::str::from_utf8;
 
         use super::{Frame, Telemetry};
+        use crate::datastore;
 
+        datastore::init();
         let mut frame = Frame::<30, 6>::default();
         let telemetry = Telemetry;
         let actual = telemetry
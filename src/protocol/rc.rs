@@ -1,5 +1,9 @@
 use crate::{
-    config, datastore,
+    config::{
+        self,
+        inputs::command::{Id, Rate},
+    },
+    datastore,
     types::control::{AxisType, Control, RSSI},
 };
 
@@ -7,6 +11,8 @@ pub const MAX_CHANNEL: usize = 18;
 
 pub struct RawControl {
     pub rssi: RSSI,
+    pub link_quality: u8, // percentage of frames received
+    pub failsafe: bool,   // receiver reported failsafe
     pub channels: [i16; MAX_CHANNEL],
 }
 
@@ -28,6 +34,23 @@ fn scale(value: i16, scale: u8) -> i16 {
     }
 }
 
+/// Zero within deadband and stretch remaining travel to full range
+fn deadband(value: i16, deadband: u8) -> i16 {
+    let band = i16::MAX as i32 * deadband as i32 / 100;
+    let value = value as i32;
+    if value.abs() <= band {
+        return 0;
+    }
+    let value = (value - band * value.signum()) * i16::MAX as i32 / (i16::MAX as i32 - band);
+    value.clamp(i16::MIN as i32, i16::MAX as i32) as i16
+}
+
+fn expo(value: i16, expo: u8) -> i16 {
+    let x = value as f32 / i16::MAX as f32;
+    let e = expo as f32 / 100.0;
+    ((x * (1.0 - e) + x * x * x * e) * i16::MAX as f32) as i16
+}
+
 fn unsigned(value: i16) -> u16 {
     (value as i32 - i16::MIN as i32) as u16
 }
@@ -45,18 +68,8 @@ impl ControlMatrix {
         }
         let mut control = Control::default();
         control.rssi = raw.rssi;
-        for (axis_type, axis) in self.axes.0.iter() {
-            if axis.channel as usize > raw.channels.len() {
-                continue;
-            }
-            let ch = scale(raw.channels[axis.channel as usize], axis.scale.0);
-            match axis_type {
-                AxisType::Throttle => control.axes.throttle = unsigned(ch),
-                AxisType::Roll => control.axes.roll = ch,
-                AxisType::Pitch => control.axes.pitch = ch,
-                AxisType::Yaw => control.axes.yaw = ch,
-            }
-        }
+        control.link_quality = raw.link_quality;
+        control.failsafe = raw.failsafe;
         for toggle in self.toggles.0.iter() {
             if toggle.channel as usize > raw.channels.len() {
                 continue;
@@ -69,6 +82,53 @@ impl ControlMatrix {
             let command = toggle.choices[index as usize];
             control.commands.push(command).ok();
         }
+        let low_rate = control.contains(Id::Rate(Rate::Low));
+        for (axis_type, axis) in self.axes.0.iter() {
+            if axis.channel as usize > raw.channels.len() {
+                continue;
+            }
+            let mut ch = deadband(raw.channels[axis.channel as usize], axis.deadband);
+            ch = expo(ch, axis.expo);
+            if low_rate {
+                ch = scale(ch, axis.low_rate);
+            }
+            let ch = scale(ch, axis.scale.0);
+            match axis_type {
+                AxisType::Throttle => control.axes.throttle = unsigned(ch),
+                AxisType::Roll => control.axes.roll = ch,
+                AxisType::Pitch => control.axes.pitch = ch,
+                AxisType::Yaw => control.axes.yaw = ch,
+                AxisType::Flaps => control.axes.flaps = unsigned(ch),
+                AxisType::Aux1 => control.aux[0] = ch,
+                AxisType::Aux2 => control.aux[1] = ch,
+                AxisType::Aux3 => control.aux[2] = ch,
+                AxisType::Aux4 => control.aux[3] = ch,
+            }
+        }
         datastore::acquire().write_control(control);
     }
 }
+
+mod test {
+    #[test]
+    fn test_deadband() {
+        use super::deadband;
+
+        assert_eq!(deadband(1000, 0), 1000);
+        assert_eq!(deadband(655, 2), 0);
+        assert_eq!(deadband(-655, 2), 0);
+        assert_eq!(deadband(1655, 2), 1020);
+        assert_eq!(deadband(i16::MAX, 2), i16::MAX);
+        assert_eq!(deadband(-i16::MAX, 2), -i16::MAX);
+    }
+
+    #[test]
+    fn test_expo() {
+        use super::expo;
+
+        assert_eq!(expo(16384, 0), 16384);
+        assert_eq!(expo(16384, 100), 4096);
+        assert_eq!(expo(-16384, 50), -10240);
+        assert_eq!(expo(i16::MAX, 30), i16::MAX);
+    }
+}
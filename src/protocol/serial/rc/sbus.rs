@@ -54,7 +54,9 @@ impl SBUS {
         }
         channels[16] = (packet.channel17 as u16 * u16::MAX) as i16;
         channels[17] = (packet.channel18 as u16 * u16::MAX) as i16;
-        Some(RawControl { rssi: 100 - self.loss_bitmap.count_ones() as u16, channels })
+        let link_quality = 100 - self.loss_bitmap.count_ones() as u8;
+        let failsafe = packet.failsafe;
+        Some(RawControl { rssi: link_quality as u16, link_quality, failsafe, channels })
     }
 
     pub fn reset(&mut self) {
@@ -84,4 +86,17 @@ mod test {
         sbus.timer = TickTimer::default();
         assert_eq!(sbus.receive(&bytes).unwrap().rssi, 100);
     }
+
+    #[test]
+    fn test_failsafe() {
+        let mut sbus = super::SBUS::new(false);
+        let mut bytes =
+            hex!("0F E0 03 1F 58 C0 07 16 B0 80 05 2C 60 01 0B F8 C0 07 00 00 00 00 00 23 00");
+        let control = sbus.receive(&bytes).unwrap();
+        assert_eq!((control.link_quality, control.failsafe), (99, false));
+        bytes[bytes.len() - 2] = 0x3C;
+        sbus.timer = TickTimer::default();
+        let control = sbus.receive(&bytes).unwrap();
+        assert_eq!((control.link_quality, control.failsafe), (98, true));
+    }
 }
@@ -0,0 +1,246 @@
+//! DShot frame consists of 11-bit value, 1-bit telemetry request and 4-bit CRC,
+//! value 1-47 are commands and 48-2047 are throttle.
+//!
+//! Bidirectional DShot inverts signal and CRC, ESC then replies eRPM on the same pin
+//! at 5/4 bit rate, 21 bits with a leading start bit, GCR encoded where level
+//! transitions stand for bit 1.
+
+use core::{
+    str::FromStr,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+pub const FRAME_SIZE: usize = 18; // 16 bits followed by 2 low bits as frame gap
+
+const MIN_THROTTLE: u16 = 48;
+const MAX_THROTTLE: u16 = 2047;
+const NO_REQUEST: u8 = u8::MAX;
+const TELEMETRY_BITS: u32 = 21;
+const INVALID: u8 = 0xFF;
+const GCR_DECODE: [u8; 32] = [
+    INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, INVALID, //
+    INVALID, 0x9, 0xA, 0xB, INVALID, 0xD, 0xE, 0xF, //
+    INVALID, INVALID, 0x2, 0x3, INVALID, 0x5, 0x6, 0x7, //
+    INVALID, 0x0, 0x8, 0x1, INVALID, 0x4, 0xC, INVALID,
+];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(u8)]
+pub enum Command {
+    MotorStop = 0,
+    Beep1 = 1,
+    Beep2 = 2,
+    Beep3 = 3,
+    Beep4 = 4,
+    Beep5 = 5,
+    SpinDirection1 = 7,
+    SpinDirection2 = 8,
+    SaveSettings = 12,
+}
+
+impl Command {
+    /// Settings commands are accepted by ESC only when repeated
+    pub fn repeat(self) -> u8 {
+        match self {
+            Self::SpinDirection1 | Self::SpinDirection2 | Self::SaveSettings => 6,
+            _ => 1,
+        }
+    }
+
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::MotorStop),
+            1 => Some(Self::Beep1),
+            2 => Some(Self::Beep2),
+            3 => Some(Self::Beep3),
+            4 => Some(Self::Beep4),
+            5 => Some(Self::Beep5),
+            7 => Some(Self::SpinDirection1),
+            8 => Some(Self::SpinDirection2),
+            12 => Some(Self::SaveSettings),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Command {
+    type Err = ();
+
+    fn from_str(string: &str) -> Result<Self, ()> {
+        match string {
+            "beep" | "beep1" => Ok(Self::Beep1),
+            "beep2" => Ok(Self::Beep2),
+            "beep3" => Ok(Self::Beep3),
+            "beep4" => Ok(Self::Beep4),
+            "beep5" => Ok(Self::Beep5),
+            "normal" => Ok(Self::SpinDirection1),
+            "reversed" => Ok(Self::SpinDirection2),
+            "save" => Ok(Self::SaveSettings),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Sends DShot frame, implemented by board with timer DMA
+pub trait DShot {
+    fn send(&mut self, frame: u16);
+
+    /// eRPM decoded from reply to last frame, bidirectional DShot only
+    fn erpm(&mut self) -> Option<u32> {
+        None
+    }
+}
+
+static REQUEST: AtomicU8 = AtomicU8::new(NO_REQUEST);
+
+/// Requests command to be sent to all DShot motors once disarmed
+pub fn request(command: Command) {
+    REQUEST.store(command as u8, Ordering::Relaxed)
+}
+
+pub fn take_request() -> Option<Command> {
+    Command::from_u8(REQUEST.swap(NO_REQUEST, Ordering::Relaxed))
+}
+
+pub fn checksum(value: u16) -> u16 {
+    (value ^ (value >> 4) ^ (value >> 8)) & 0xF
+}
+
+pub fn to_frame(value: u16, telemetry: bool) -> u16 {
+    let value = (value & 0x7FF) << 1 | telemetry as u16;
+    value << 4 | checksum(value)
+}
+
+pub fn to_bidirectional_frame(value: u16, telemetry: bool) -> u16 {
+    let value = (value & 0x7FF) << 1 | telemetry as u16;
+    value << 4 | (!checksum(value) & 0xF)
+}
+
+/// Maps throttle to [48, 2047], zero throttle stops motor
+pub fn to_throttle(value: u16) -> u16 {
+    if value == 0 {
+        return 0;
+    }
+    let range = (MAX_THROTTLE - MIN_THROTTLE) as u32;
+    MIN_THROTTLE + (value as u32 * range / u16::MAX as u32) as u16
+}
+
+/// Encodes frame MSB first as timer compare values of each bit period,
+/// bit 1 holds high for 3/4 of bit period while bit 0 for 3/8
+pub fn encode(frame: u16, max_duty: u16, buffer: &mut [u32]) {
+    let (one, zero) = (max_duty as u32 * 3 / 4, max_duty as u32 * 3 / 8);
+    for (i, duty) in buffer.iter_mut().enumerate() {
+        *duty = match i {
+            _ if i >= 16 => 0,
+            _ if frame & (0x8000 >> i) != 0 => one,
+            _ => zero,
+        };
+    }
+}
+
+/// Decodes reply from timestamps of captured edges, in timer ticks of 16-bit timer
+pub fn decode_erpm(edges: &[u32], bit_period: u32) -> Option<u32> {
+    if edges.is_empty() || bit_period == 0 {
+        return None;
+    }
+    let mut value = 0u32;
+    let mut bits = 0;
+    for window in edges.windows(2) {
+        let ticks = (window[1] as u16).wrapping_sub(window[0] as u16) as u32;
+        let length = (ticks + bit_period / 2) / bit_period;
+        if length == 0 || bits + length >= TELEMETRY_BITS {
+            return None;
+        }
+        value = value << length | 1 << (length - 1);
+        bits += length;
+    }
+    let length = TELEMETRY_BITS - bits; // Line stays idle after last edge
+    value = value << length | 1 << (length - 1);
+
+    let mut decoded = 0u16;
+    for i in (0..4).rev() {
+        let nibble = GCR_DECODE[(value >> (i * 5)) as usize & 0x1F];
+        if nibble == INVALID {
+            return None;
+        }
+        decoded = decoded << 4 | nibble as u16;
+    }
+    if (decoded ^ decoded >> 4 ^ decoded >> 8 ^ decoded >> 12) & 0xF != 0xF {
+        return None;
+    }
+    let value = decoded >> 4; // 3-bit exponent and 9-bit mantissa of period in microseconds
+    if value == 0xFFF {
+        return Some(0); // Motor stopped
+    }
+    match ((value & 0x1FF) as u32) << (value >> 9) {
+        0 => None,
+        period => Some(60_000_000 / period),
+    }
+}
+
+mod test {
+    #[test]
+    fn test_dshot_frame() {
+        use super::{to_frame, to_throttle, Command};
+
+        assert_eq!(to_throttle(0), 0);
+        assert_eq!(to_throttle(1), 48);
+        assert_eq!(to_throttle(u16::MAX / 2), 1047);
+        assert_eq!(to_throttle(u16::MAX), 2047);
+
+        assert_eq!(to_frame(1046, false), 0x82C6);
+        assert_eq!(to_frame(1046, true), 0x82D7);
+        assert_eq!(to_frame(Command::Beep1 as u16, false), 0x0022);
+        assert_eq!(to_frame(Command::SaveSettings as u16, true), 0x0198);
+    }
+
+    #[test]
+    fn test_dshot_encode() {
+        use super::{encode, FRAME_SIZE};
+
+        let mut buffer = [0u32; FRAME_SIZE];
+        encode(0x82C6, 80, &mut buffer);
+        let expected = [60, 30, 30, 30, 30, 30, 60, 30, 60, 60, 30, 30, 30, 60, 60, 30, 0, 0];
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn test_dshot_bidirectional_frame() {
+        use super::to_bidirectional_frame;
+
+        assert_eq!(to_bidirectional_frame(1046, false), 0x82C9);
+        assert_eq!(to_bidirectional_frame(0, false), 0x000F);
+    }
+
+    #[test]
+    fn test_decode_erpm() {
+        use super::decode_erpm;
+
+        // Captured reply of period 1000us at DShot600 with 84MHz timer clock
+        let edges = [
+            1003, 1107, 1455, 1558, 1788, 1890, 2009, 2125, 2229, 2346, 2452, 2686, 2791, 3019,
+            3121, 3242,
+        ];
+        assert_eq!(decode_erpm(&edges, 112), Some(60000));
+        // Should handle timer counter wrap
+        let wrapped = edges.map(|edge| (edge + 64000) & 0xFFFF);
+        assert_eq!(decode_erpm(&wrapped, 112), Some(60000));
+        // Should reject corrupted reply
+        let mut corrupted = edges;
+        corrupted[5] += 112;
+        assert_eq!(decode_erpm(&corrupted, 112), None);
+        assert_eq!(decode_erpm(&edges[..15], 112), None);
+        assert_eq!(decode_erpm(&[], 112), None);
+    }
+
+    #[test]
+    #[serial]
+    fn test_dshot_request() {
+        use super::{request, take_request, Command};
+
+        assert_eq!(take_request(), None);
+        request(Command::SaveSettings);
+        assert_eq!(take_request(), Some(Command::SaveSettings));
+        assert_eq!(take_request(), None);
+    }
+}
@@ -1 +1,3 @@
+pub mod dshot;
+pub mod out;
 pub mod pwm;
@@ -0,0 +1,5 @@
+use heapless::Vec;
+
+/// Motor speed in revolutions per minute ordered by motor index
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct MotorRPM(pub Vec<u32, 8>);
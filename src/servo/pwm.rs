@@ -1,52 +1,112 @@
 use alloc::{boxed::Box, vec::Vec};
+use core::cmp;
 
 use embedded_hal::PwmPin;
+use fugit::NanosDurationU64 as Duration;
 use heapless::LinearMap;
 
-use crate::{config::peripherals::pwm as config, datastore, fcs::out::Configuration};
+use super::{
+    dshot::{self, Command, DShot},
+    out::MotorRPM,
+};
+use crate::{
+    config::peripherals::pwm as config,
+    datastore,
+    fcs::{arming::State, out::Configuration},
+    sys::jiffies,
+};
 
-type PWM = Box<dyn PwmPin<Duty = u16> + Send>;
+pub enum Output {
+    /// With frame rate its timer actually runs at
+    PWM(Box<dyn PwmPin<Duty = u16> + Send>, u32),
+    DShot(Box<dyn DShot + Send>),
+}
+
+const AUX_SERVOS: [config::ServoType; 4] = [
+    config::ServoType::Aux1,
+    config::ServoType::Aux2,
+    config::ServoType::Aux3,
+    config::ServoType::Aux4,
+];
+
+struct Servo {
+    config: config::Servo,
+    index: usize,
+    position: Option<(i32, Duration)>,
+}
 
 pub struct PWMs<'a> {
     config_iteration: usize,
-    pwms: Vec<(&'a str, PWM)>,
-    motors: heapless::Vec<(config::Motor, usize), 4>,
-    servos: LinearMap<config::ServoType, (config::Servo, usize), 4>,
+    pwms: Vec<(&'a str, Output)>,
+    motors: heapless::Vec<(config::Motor, usize), 8>,
+    servos: LinearMap<config::ServoType, Servo, 8>,
+    command: Option<(Command, u8)>,
 }
 
-fn to_motor_pwm_duty(max_duty: u16, rate: u16, value: u16) -> u16 {
-    let duty_per_ms = max_duty as u32 * rate as u32 / 1000;
-    (duty_per_ms + duty_per_ms * value as u32 / u16::MAX as u32) as u16
+/// Scales throttle to pulse width range of analog protocol within frame period of rate
+fn to_motor_pwm_duty(max_duty: u16, protocol: config::Protocol, rate: u32, value: u16) -> u16 {
+    let (min, max) = match protocol.pulse_width() {
+        Some(range) => range,
+        None => return 0,
+    };
+    let pulse = min as u64 + (max - min) as u64 * value as u64 / u16::MAX as u64;
+    (max_duty as u64 * pulse * rate as u64 / 1_000_000_000) as u16
 }
 
 fn angle_to_axis(angle: i8) -> i32 {
     angle as i32 * i16::MAX as i32 / 90
 }
 
-fn to_servo_pwm_duty(max_duty: u16, value: i16, min: i8, max: i8, reversed: bool) -> u16 {
-    let base = max_duty / 40; // 0.5ms
-    let range = (max_duty / 10) as u32; // 2.0ms
-    let offset = angle_to_axis((max + min) / 2);
-    let mut value = if reversed { -value } else { value } as i32 + offset;
-    let max = angle_to_axis(max);
-    if value > max {
-        value = max;
+/// Linear interpolation between curve points evenly spaced over [-32767, 32767]
+fn apply_curve(curve: &[i8], value: i16) -> i32 {
+    let value = cmp::max(value, -i16::MAX) as i32;
+    if curve.len() < 2 {
+        return value;
     }
-    let min = angle_to_axis(min);
-    if value < min {
-        value = min;
+    let segments = (curve.len() - 1) as i32;
+    let scaled = (value + i16::MAX as i32) * segments; // [0, 65534 * segments]
+    let width = 2 * i16::MAX as i32;
+    let index = cmp::min(scaled / width, segments - 1);
+    let (from, to) = (curve[index as usize] as i32, curve[index as usize + 1] as i32);
+    let percent = from * width + (to - from) * (scaled - index * width); // scaled by width
+    percent / 200 // percent * 32767 / 100 / width
+}
+
+/// Position after curve, reverse and angle limits, within [-32767, 32767] as [-90°, 90°]
+fn to_servo_position(value: i16, servo: &config::Servo) -> i32 {
+    let value = apply_curve(&servo.curve, value);
+    let offset = angle_to_axis((servo.max_angle + servo.min_angle) / 2);
+    let value = if servo.reversed { -value } else { value } + offset;
+    cmp::max(cmp::min(value, angle_to_axis(servo.max_angle)), angle_to_axis(servo.min_angle))
+}
+
+/// Limits position change by slew rate in degree per second, 0 disables
+fn slew(from: i32, to: i32, rate: u16, elapsed: Duration) -> i32 {
+    if rate == 0 {
+        return to;
     }
-    let signed = value as i32 + i16::MAX as i32 + 1; // [-32767, 32767] => [1, 65535]
-    base + (range * (signed as u32) / u16::MAX as u32) as u16
+    let max_delta = rate as u64 * i16::MAX as u64 * elapsed.to_micros() / 90 / 1_000_000;
+    let max_delta = cmp::min(max_delta, u16::MAX as u64) as i32;
+    cmp::max(cmp::min(to, from + max_delta), from - max_delta)
+}
+
+/// Maps position to pulse between endpoints with subtrim at timer frame rate
+fn to_servo_pwm_duty(max_duty: u16, rate: u32, position: i32, servo: &config::Servo) -> u16 {
+    let (min, max) = (servo.min_pulse as i32, servo.max_pulse as i32);
+    let center = (min + max) / 2 + servo.subtrim as i32;
+    let pulse = center + position * (max - min) / 2 / i16::MAX as i32;
+    let pulse = cmp::max(cmp::min(pulse, max), min) as u64;
+    (max_duty as u64 * pulse * rate as u64 / 1_000_000) as u16
 }
 
 impl<'a> PWMs<'a> {
-    pub fn new(pwms: Vec<(&'a str, PWM)>) -> Self {
+    pub fn new(pwms: Vec<(&'a str, Output)>) -> Self {
         Self {
             config_iteration: 0,
             pwms,
             motors: heapless::Vec::new(),
             servos: heapless::LinearMap::new(),
+            command: None,
         }
     }
 
@@ -54,48 +114,133 @@ impl<'a> PWMs<'a> {
         self.servos.clear();
         self.motors.clear();
         let config = crate::config::get();
-        for (&id, &config) in config.peripherals.pwms.0.iter() {
+        for (&id, config) in config.peripherals.pwms.0.iter() {
             let index = match self.pwms.iter().enumerate().find(|(_, (n, _))| id.equals_str(n)) {
                 Some((index, _)) => index,
                 None => continue,
             };
             match config {
                 config::PWM::Motor(motor) => {
-                    self.motors.push((motor, index)).ok();
+                    self.motors.push((*motor, index)).ok();
                 }
                 config::PWM::Servo(servo) => {
-                    self.servos.insert(servo.servo_type, (servo, index)).ok();
+                    let servo_type = servo.servo_type;
+                    let servo = Servo { config: servo.clone(), index, position: None };
+                    self.servos.insert(servo_type, servo).ok();
                 }
             }
         }
         self.motors.sort_by(|a, b| a.0.index.partial_cmp(&b.0.index).unwrap());
     }
 
-    pub fn update(&mut self) {
-        if self.config_iteration != crate::config::iteration() {
-            self.reconfigure();
+    /// Command takes place of throttle until repeated enough, only while disarmed
+    fn next_command(&mut self, armed: bool) -> Option<Command> {
+        if armed {
+            self.command = None;
+            return None;
         }
-        match datastore::acquire().read_fcs().control {
-            Configuration::FixedWing(fixed_wing) => {
-                for (i, &value) in fixed_wing.engines.iter().enumerate() {
-                    if let Some(&(motor, index)) = self.motors.get(i) {
-                        let (_, ref mut pwm) = &mut self.pwms[index];
-                        let max_duty = pwm.get_max_duty();
-                        pwm.set_duty(to_motor_pwm_duty(max_duty, motor.rate, value));
-                    }
+        if self.command.is_none() {
+            self.command = dshot::take_request().map(|command| (command, command.repeat()));
+        }
+        let (command, remain) = self.command.as_mut()?;
+        let command = *command;
+        *remain -= 1;
+        if *remain == 0 {
+            self.command = None;
+        }
+        Some(command)
+    }
+
+    fn write_motors(&mut self, values: &[u16], armed: bool) {
+        let command = self.next_command(armed);
+        let mut rpms: heapless::Vec<u32, 8> = heapless::Vec::new();
+        for (i, &value) in values.iter().enumerate() {
+            let value = if armed { value } else { 0 };
+            let &(motor, index) = match self.motors.get(i) {
+                Some(tuple) => tuple,
+                None => continue,
+            };
+            match (motor.protocol, &mut self.pwms[index].1) {
+                (protocol, Output::PWM(pwm, rate)) if protocol.pulse_width().is_some() => {
+                    let max_duty = pwm.get_max_duty();
+                    pwm.set_duty(to_motor_pwm_duty(max_duty, protocol, *rate, value));
                 }
-                for &(servo_type, value) in fixed_wing.control_surface.iter() {
-                    let (servo, index) = match self.servos.get(&servo_type) {
-                        Some(tuple) => tuple,
-                        None => continue,
+                (protocol, Output::DShot(output)) if protocol.bitrate().is_some() => {
+                    let (value, telemetry) = match command {
+                        Some(command) => (command as u16, true),
+                        None => (dshot::to_throttle(value), false),
                     };
-                    let (_, ref mut pwm) = &mut self.pwms[*index];
-                    let max_duty = pwm.get_max_duty();
-                    let (min, max) = (servo.min_angle, servo.max_angle);
-                    pwm.set_duty(to_servo_pwm_duty(max_duty, value, min, max, servo.reversed));
+                    if !motor.bidirectional {
+                        output.send(dshot::to_frame(value, telemetry));
+                        continue;
+                    }
+                    output.send(dshot::to_bidirectional_frame(value, telemetry));
+                    let rpm = output.erpm().map(|erpm| erpm * 2 / motor.poles as u32);
+                    rpms.resize(i + 1, 0).ok();
+                    rpms[i] = rpm.unwrap_or_default();
                 }
+                _ => (),
+            }
+        }
+        if !rpms.is_empty() {
+            datastore::acquire().write_motor_rpm(MotorRPM(rpms));
+        }
+    }
+
+    fn update_servo(&mut self, now: Duration, servo_type: config::ServoType, value: i16) {
+        let servo = match self.servos.get_mut(&servo_type) {
+            Some(servo) => servo,
+            None => return,
+        };
+        let mut position = to_servo_position(value, &servo.config);
+        if let Some((last, timestamp)) = servo.position {
+            position = slew(last, position, servo.config.slew_rate, now - timestamp);
+        }
+        servo.position = Some((position, now));
+        let (pwm, rate) = match &mut self.pwms[servo.index].1 {
+            Output::PWM(pwm, rate) => (pwm, *rate),
+            Output::DShot(_) => return,
+        };
+        let max_duty = pwm.get_max_duty();
+        pwm.set_duty(to_servo_pwm_duty(max_duty, rate, position, &servo.config));
+    }
+
+    fn check_config(&mut self) {
+        if self.config_iteration != crate::config::iteration() {
+            self.config_iteration = crate::config::iteration();
+            self.reconfigure();
+        }
+    }
+
+    /// Scheduled at motor rate, so that DShot and its eRPM reply keep up with gyro
+    pub fn update_motors(&mut self) {
+        self.check_config();
+        if self.motors.is_empty() {
+            return;
+        }
+        let fcs = datastore::acquire().read_fcs();
+        let armed = fcs.arming == State::Armed;
+        match fcs.control {
+            Configuration::FixedWing(fixed_wing) => self.write_motors(&fixed_wing.engines, armed),
+            Configuration::Multirotor(multirotor) => self.write_motors(&multirotor.motors, armed),
+        }
+    }
+
+    /// Scheduled at servo rate
+    pub fn update_servos(&mut self) {
+        self.check_config();
+        let now = jiffies::get();
+        let ds = datastore::acquire();
+        if let Configuration::FixedWing(fixed_wing) = ds.read_fcs().control {
+            for &(servo_type, value) in fixed_wing.control_surface.iter() {
+                self.update_servo(now, servo_type, value);
             }
         }
+        // Auxiliary channels bypass FCS and hold last value on link loss
+        let aux = ds.read_control().aux;
+        for (&servo_type, &value) in AUX_SERVOS.iter().zip(aux.iter()) {
+            self.update_servo(now, servo_type, value);
+        }
     }
 }
 
@@ -103,23 +248,108 @@ mod test {
     #[test]
     fn test_to_motor_pwm_duty() {
         use super::to_motor_pwm_duty;
+        use crate::config::Protocol;
 
         let max_duty = 20000;
-        assert_eq!(to_motor_pwm_duty(max_duty, 400, 0), 8000);
-        assert_eq!(to_motor_pwm_duty(max_duty, 400, u16::MAX / 2), 11999);
-        assert_eq!(to_motor_pwm_duty(max_duty, 400, u16::MAX), 16000);
+        assert_eq!(to_motor_pwm_duty(max_duty, Protocol::PWM, 400, 0), 8000);
+        assert_eq!(to_motor_pwm_duty(max_duty, Protocol::PWM, 400, u16::MAX / 2), 11999);
+        assert_eq!(to_motor_pwm_duty(max_duty, Protocol::PWM, 400, u16::MAX), 16000);
+
+        assert_eq!(to_motor_pwm_duty(max_duty, Protocol::OneShot125, 2000, 0), 5000);
+        assert_eq!(to_motor_pwm_duty(max_duty, Protocol::OneShot125, 2000, u16::MAX), 10000);
+        assert_eq!(to_motor_pwm_duty(max_duty, Protocol::OneShot42, 8000, 0), 6720);
+        assert_eq!(to_motor_pwm_duty(max_duty, Protocol::OneShot42, 8000, u16::MAX), 13440);
+        assert_eq!(to_motor_pwm_duty(max_duty, Protocol::MultiShot, 32000, 0), 3200);
+        assert_eq!(to_motor_pwm_duty(max_duty, Protocol::MultiShot, 32000, u16::MAX / 2), 9599);
+        assert_eq!(to_motor_pwm_duty(max_duty, Protocol::MultiShot, 32000, u16::MAX), 16000);
+        assert_eq!(to_motor_pwm_duty(max_duty, Protocol::DShot600, 400, u16::MAX), 0);
+    }
+
+    #[test]
+    #[serial]
+    fn test_dshot_command() {
+        use alloc::vec::Vec;
+
+        use super::PWMs;
+        use crate::servo::dshot::{request, Command};
+
+        let mut pwms = PWMs::new(Vec::new());
+        assert_eq!(pwms.next_command(false), None);
+        request(Command::Beep1);
+        assert_eq!(pwms.next_command(false), Some(Command::Beep1));
+        assert_eq!(pwms.next_command(false), None);
+
+        // Should repeat settings command
+        request(Command::SaveSettings);
+        for _ in 0..6 {
+            assert_eq!(pwms.next_command(false), Some(Command::SaveSettings));
+        }
+        assert_eq!(pwms.next_command(false), None);
+
+        // Should drop command once armed
+        request(Command::SpinDirection2);
+        assert_eq!(pwms.next_command(false), Some(Command::SpinDirection2));
+        assert_eq!(pwms.next_command(true), None);
+        assert_eq!(pwms.next_command(false), None);
     }
 
     #[test]
     fn test_to_servo_pwm_duty() {
-        use super::to_servo_pwm_duty;
+        use super::{to_servo_position, to_servo_pwm_duty};
+        use crate::config::peripherals::pwm::{Servo, ServoType};
 
         let max_duty = 180 * 10;
+        let mut servo = Servo::of(ServoType::Elevator);
+        let duty = |value, servo: &Servo| {
+            to_servo_pwm_duty(max_duty, 50, to_servo_position(value, servo), servo)
+        };
         let center = max_duty / 40 + max_duty / 20; // 0.5ms + 1.0ms
-        assert_eq!(to_servo_pwm_duty(max_duty, 0, -90, 90, false), center);
-        assert_eq!(to_servo_pwm_duty(max_duty, -32768, -90, 90, false), center - 90);
-        assert_eq!(to_servo_pwm_duty(max_duty, 32767, -90, 90, false), center + 90);
-        assert_eq!(to_servo_pwm_duty(max_duty, -8192, -90, 90, false), center - 23);
-        assert_eq!(to_servo_pwm_duty(max_duty, 8192, -90, 90, false), center + 22);
+        assert_eq!(duty(0, &servo), center);
+        assert_eq!(duty(-32768, &servo), center - 90);
+        assert_eq!(duty(32767, &servo), center + 90);
+        assert_eq!(duty(-8192, &servo), center - 23);
+        assert_eq!(duty(8192, &servo), center + 22);
+
+        // Should shift center by subtrim and stop at pulse endpoints
+        servo.subtrim = 100;
+        servo.min_pulse = 1000;
+        servo.max_pulse = 2000;
+        assert_eq!(duty(0, &servo), center + 9);
+        assert_eq!(duty(-32768, &servo), center - 36);
+        assert_eq!(duty(32767, &servo), center + 45);
+
+        // Should scale pulse by timer frame rate, 1.6ms of 3ms
+        let position = to_servo_position(0, &servo);
+        assert_eq!(to_servo_pwm_duty(max_duty, 333, position, &servo), 959);
+    }
+
+    #[test]
+    fn test_servo_curve() {
+        use super::to_servo_position;
+        use crate::config::peripherals::pwm::{Servo, ServoType};
+
+        let mut servo = Servo::of(ServoType::Elevator);
+        servo.curve.extend_from_slice(&[-100, -20, 0, 20, 100]).ok();
+        assert_eq!(to_servo_position(0, &servo), 0);
+        assert_eq!(to_servo_position(8192, &servo), 3276);
+        assert_eq!(to_servo_position(16384, &servo), 6554);
+        assert_eq!(to_servo_position(24575, &servo), 19659);
+        assert_eq!(to_servo_position(i16::MAX, &servo), 32767);
+        assert_eq!(to_servo_position(i16::MIN, &servo), -32767);
+        servo.reversed = true;
+        assert_eq!(to_servo_position(8192, &servo), -3276);
+    }
+
+    #[test]
+    fn test_servo_slew() {
+        use fugit::NanosDurationU64 as Duration;
+
+        use super::slew;
+
+        assert_eq!(slew(0, 32767, 0, Duration::millis(20)), 32767);
+        // 90 degree per second within 20ms
+        assert_eq!(slew(0, 32767, 90, Duration::millis(20)), 655);
+        assert_eq!(slew(0, -32767, 90, Duration::millis(20)), -655);
+        assert_eq!(slew(0, 100, 90, Duration::millis(20)), 100);
     }
 }
@@ -12,6 +12,11 @@ pub enum AxisType {
     Roll,
     Pitch,
     Yaw,
+    Flaps,
+    Aux1,
+    Aux2,
+    Aux3,
+    Aux4,
 }
 
 impl FromStr for AxisType {
@@ -23,6 +28,11 @@ impl FromStr for AxisType {
             "roll" => Ok(Self::Roll),
             "pitch" => Ok(Self::Pitch),
             "yaw" => Ok(Self::Yaw),
+            "flaps" => Ok(Self::Flaps),
+            "aux1" => Ok(Self::Aux1),
+            "aux2" => Ok(Self::Aux2),
+            "aux3" => Ok(Self::Aux3),
+            "aux4" => Ok(Self::Aux4),
             _ => Err(()),
         }
     }
@@ -36,11 +46,21 @@ pub struct Axes {
     pub roll: i16,
     pub pitch: i16,
     pub yaw: i16,
+    pub flaps: u16,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Control {
     pub rssi: RSSI,
+    pub link_quality: u8,
+    pub failsafe: bool,
     pub axes: Axes,
+    pub aux: [i16; 4],
     pub commands: Vec<command::Id, 8>,
 }
+
+impl Control {
+    pub fn contains(&self, id: command::Id) -> bool {
+        self.commands.contains(&id)
+    }
+}
@@ -1,19 +1,21 @@
+use heapless::String;
+
 use super::coordinate::Position;
 
-#[derive(Copy, Clone, Debug, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Waypoint {
-    pub name: &'static str,
+    pub name: String<8>,
     pub position: Position,
 }
 
 impl Default for Waypoint {
     fn default() -> Self {
-        Self { name: "HOME", position: Default::default() }
+        Self { name: String::from("HOME"), position: Default::default() }
     }
 }
 
-#[derive(Copy, Clone, Debug, Default, Serialize)]
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct Steerpoint {
-    pub index: u8,
+    pub index: u8, // 0 stands for home
     pub waypoint: Waypoint,
 }